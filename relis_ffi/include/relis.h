@@ -0,0 +1,70 @@
+/* C interface of relis: parse AMBER MD .lis files into Arrow arrays (Arrow C data interface).
+ *
+ * Build with `cargo build --release -p relis_ffi`, then link with target/release/librelis_ffi.so (or .a).
+ *
+ * The returned struct array has one float64 child per field (TIME(PS), Etot, ...), one row per frame.
+ * It can be imported by any Arrow implementation, e.g. arrow::ImportRecordBatch in C++.
+ *
+ * From Julia:
+ *   status = ccall((:relis_parse_file, "librelis_ffi"), Cint,
+ *                  (Cstring, Ptr{Cvoid}, Ptr{Cvoid}, Ptr{Cvoid}, Ptr{Cvoid}, Ptr{Cvoid}),
+ *                  "prod1.lis", C_NULL, C_NULL, C_NULL, array_ptr, schema_ptr)
+ */
+#ifndef RELIS_H
+#define RELIS_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#ifndef ARROW_C_DATA_INTERFACE
+#define ARROW_C_DATA_INTERFACE
+
+#define ARROW_FLAG_DICTIONARY_ORDERED 1
+#define ARROW_FLAG_NULLABLE 2
+#define ARROW_FLAG_MAP_KEYS_SORTED 4
+
+struct ArrowSchema {
+  const char* format;
+  const char* name;
+  const char* metadata;
+  int64_t flags;
+  int64_t n_children;
+  struct ArrowSchema** children;
+  struct ArrowSchema* dictionary;
+  void (*release)(struct ArrowSchema*);
+  void* private_data;
+};
+
+struct ArrowArray {
+  int64_t length;
+  int64_t null_count;
+  int64_t offset;
+  int64_t n_buffers;
+  int64_t n_children;
+  const void** buffers;
+  struct ArrowArray** children;
+  struct ArrowArray* dictionary;
+  void (*release)(struct ArrowArray*);
+  void* private_data;
+};
+
+#endif /* ARROW_C_DATA_INTERFACE */
+
+/* Parse a .lis file. start_pattern, end_pattern and regex may be NULL to use the defaults
+ * ("RESULTS", "A V E R A G E" and the regex of relis).
+ * Returns 0 and fills out_array and out_schema (to be released by the caller with their release callbacks),
+ * or -1 on failure, see relis_last_error. */
+int relis_parse_file(const char* path, const char* start_pattern, const char* end_pattern, const char* regex,
+                     struct ArrowArray* out_array, struct ArrowSchema* out_schema);
+
+/* Message of the last error of the calling thread, or NULL. Owned by relis. */
+const char* relis_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* RELIS_H */
@@ -0,0 +1,86 @@
+// C interface of relis: the values of a .lis file are returned as Arrow arrays, using the Arrow C data interface
+// (https://arrow.apache.org/docs/format/CDataInterface.html), so that C/C++ codes, or Julia through ccall,
+// can use them without going through files. The declarations for C are in include/relis.h.
+use std::cell::RefCell;
+use std::error::Error;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::path::Path;
+use polars::export::arrow::array::StructArray;
+use polars::export::arrow::datatypes::{DataType, Field};
+use polars::export::arrow::ffi::{export_array_to_c, export_field_to_c, ArrowArray, ArrowSchema};
+use regex::Regex;
+
+thread_local! {
+    // Message of the last error of the calling thread.
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Parse a .lis file into a struct array with one child array (float64) per field.
+/// `start_pattern`, `end_pattern` and `regex` may be NULL to use the defaults of relis.
+/// On success, returns 0 and moves the array and its schema into `out_array` and `out_schema`,
+/// the caller must call their release callbacks when done.
+/// On failure, returns -1 and the message can be read with `relis_last_error`.
+///
+/// # Safety
+/// The strings must be NULL or valid NUL terminated strings, the output pointers must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn relis_parse_file(
+    path: *const c_char,
+    start_pattern: *const c_char,
+    end_pattern: *const c_char,
+    regex: *const c_char,
+    out_array: *mut ArrowArray,
+    out_schema: *mut ArrowSchema,
+) -> c_int {
+    let result = (|| {
+        if out_array.is_null() || out_schema.is_null() {
+            return Err("the output pointers must not be NULL".into());
+        }
+        let path = to_str(path)?.ok_or("the path must not be NULL")?;
+        let start = Regex::new(to_str(start_pattern)?.unwrap_or(relis::DEFAULT_START_PATTERN))?;
+        let end = Regex::new(to_str(end_pattern)?.unwrap_or(relis::DEFAULT_END_PATTERN))?;
+        let re = Regex::new(to_str(regex)?.unwrap_or(relis::DEFAULT_REGEX))?;
+        export(Path::new(path), &start, &end, &re)
+    })();
+    match result {
+        Ok((array, schema)) => {
+            out_array.write(array);
+            out_schema.write(schema);
+            0
+        }
+        Err(e) => {
+            set_last_error(e);
+            -1
+        }
+    }
+}
+
+/// Returns the message of the last error of the calling thread, or NULL.
+/// The string belongs to relis and stays valid until the next call to relis from the thread.
+#[no_mangle]
+pub extern "C" fn relis_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(std::ptr::null(), |e| e.as_ptr()))
+}
+
+fn export(path: &Path, start: &Regex, end: &Regex, re: &Regex) -> Result<(ArrowArray, ArrowSchema), Box<dyn Error>> {
+    let mut df = relis::parse_file(path, start, end, re)?;
+    df.rechunk();
+    let fields: Vec<Field> = df.get_columns().iter().map(|s| s.field().to_arrow()).collect();
+    let arrays = df.get_columns().iter().map(|s| s.to_arrow(0)).collect();
+    let dtype = DataType::Struct(fields);
+    let array = StructArray::new(dtype.clone(), arrays, None);
+    let field = Field::new("relis", dtype, false);
+    Ok((export_array_to_c(Box::new(array)), export_field_to_c(&field)))
+}
+
+unsafe fn to_str<'a>(s: *const c_char) -> Result<Option<&'a str>, Box<dyn Error>> {
+    if s.is_null() {
+        return Ok(None);
+    }
+    Ok(Some(CStr::from_ptr(s).to_str()?))
+}
+
+fn set_last_error(e: Box<dyn Error>) {
+    let message = CString::new(e.to_string().replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
@@ -0,0 +1,72 @@
+// WebAssembly interface of relis, to read .lis files in a web browser without sending them anywhere.
+// Build with: wasm-pack build --target web relis_wasm, then serve relis_wasm/ and open www/index.html.
+// The functions take the content of the file, as given by File.arrayBuffer(), and return JSON.
+use std::collections::BTreeMap;
+use regex::Regex;
+use relis::RelisError;
+use serde_json::{json, Value};
+use wasm_bindgen::prelude::*;
+
+/// Extract the values of a .lis file, returns a JSON object with an array of values per field.
+/// The patterns and the regex are optional, the defaults of relis are used when they are not given.
+#[wasm_bindgen]
+pub fn parse(bytes: &[u8], start_pattern: Option<String>, end_pattern: Option<String>, regex: Option<String>) -> Result<String, JsError> {
+    let data = parse_with(bytes, start_pattern, end_pattern, regex).map_err(to_js)?;
+    Ok(json!(data).to_string())
+}
+
+/// Summarize a .lis file, returns a JSON object with the number of frames and the mean and standard deviation
+/// of each field: {"frames": 10, "columns": {"Etot": {"mean": ..., "std": ...}, ...}}.
+#[wasm_bindgen]
+pub fn summarize(bytes: &[u8], start_pattern: Option<String>, end_pattern: Option<String>, regex: Option<String>) -> Result<String, JsError> {
+    let data = parse_with(bytes, start_pattern, end_pattern, regex).map_err(to_js)?;
+    let frames = data.values().map(Vec::len).max().unwrap_or(0);
+    let columns: serde_json::Map<String, Value> = data
+        .iter()
+        .map(|(name, values)| {
+            let (mean, std) = mean_std(values);
+            (name.clone(), json!({ "mean": mean, "std": std }))
+        })
+        .collect();
+    Ok(json!({ "frames": frames, "columns": columns }).to_string())
+}
+
+fn parse_with(bytes: &[u8], start_pattern: Option<String>, end_pattern: Option<String>, regex: Option<String>)
+    -> Result<BTreeMap<String, Vec<f64>>, RelisError> {
+    let start = Regex::new(start_pattern.as_deref().unwrap_or(relis::DEFAULT_START_PATTERN))?;
+    let end = Regex::new(end_pattern.as_deref().unwrap_or(relis::DEFAULT_END_PATTERN))?;
+    let re = Regex::new(regex.as_deref().unwrap_or(relis::DEFAULT_REGEX))?;
+    relis::parse_bytes(bytes, &start, &end, &re)
+}
+
+// Mean and sample standard deviation, as computed by the command line tool (null in JSON when undefined).
+// The frames missing the field (NaN) are left out, as the command line tool leaves out the missing values.
+fn mean_std(values: &[f64]) -> (Option<f64>, Option<f64>) {
+    let values: Vec<f64> = values.iter().copied().filter(|v| !v.is_nan()).collect();
+    if values.is_empty() {
+        return (None, None);
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    if values.len() < 2 {
+        return (Some(mean), None);
+    }
+    let var = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    (Some(mean), Some(var.sqrt()))
+}
+
+fn to_js(e: RelisError) -> JsError {
+    JsError::new(&e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_values_are_left_out() {
+        assert_eq!(mean_std(&[1.0, f64::NAN, 3.0]), (Some(2.0), Some(2f64.sqrt())));
+        assert_eq!(mean_std(&[f64::NAN, 5.0]), (Some(5.0), None));
+        assert_eq!(mean_std(&[f64::NAN]), (None, None));
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Instant;
+use clap::Args;
+use crate::watch::WatchedFile;
+
+#[derive(Args)]
+pub struct AlertArgs {
+    /// Shell command run when an alert fires, with the message in $RELIS_ALERT and the file in $RELIS_FILE,
+    /// e.g. 'mail -s "$RELIS_ALERT" me@lab.org < /dev/null'. A NaN energy always fires an alert.
+    #[arg(long, value_name = "CMD")]
+    alert_cmd: Option<String>,
+    /// URL to which the alerts are posted with curl, as JSON {"text": "..."} (e.g. a Slack or Mattermost webhook).
+    #[arg(long, value_name = "URL")]
+    alert_webhook: Option<String>,
+    /// Fire an alert when the temperature of a frame is more than K away from the mean of the frames before it.
+    #[arg(long, value_name = "K")]
+    alert_temp_spike: Option<f64>,
+    /// Fire an alert when the density moves by more than this fraction (e.g. 0.05) from the mean of the first 10 frames.
+    #[arg(long, value_name = "FRACTION")]
+    alert_density_drift: Option<f64>,
+    /// Fire an alert when a run that has not ended has not grown for this many seconds (e.g. a crashed GPU job).
+    #[arg(long, value_name = "SECONDS")]
+    alert_stall: Option<f64>,
+}
+
+// Number of frames from which the reference density is taken.
+const REFERENCE_FRAMES: usize = 10;
+
+// What is known of a watched file to check it for anomalies.
+struct Checked {
+    // Number of frames already checked.
+    frames: usize,
+    // Sum of the temperatures of the frames checked, and their number.
+    temperatures: (f64, usize),
+    // When the number of frames last changed.
+    grown: Instant,
+}
+
+// The conditions checked on the watched files. Each condition fires once per file, not at every check.
+pub struct Alerts<'a> {
+    args: &'a AlertArgs,
+    checked: BTreeMap<PathBuf, Checked>,
+    fired: BTreeSet<(PathBuf, &'static str)>,
+}
+
+impl<'a> Alerts<'a> {
+    pub fn new(args: &'a AlertArgs) -> Alerts<'a> {
+        Alerts { args, checked: BTreeMap::new(), fired: BTreeSet::new() }
+    }
+
+    fn enabled(&self) -> bool {
+        self.args.alert_cmd.is_some() || self.args.alert_webhook.is_some()
+    }
+
+    // Check the new frames of the files and fire the alerts of the conditions met.
+    pub fn check(&mut self, files: &BTreeMap<PathBuf, WatchedFile>) {
+        if !self.enabled() {
+            return;
+        }
+        for (path, watched) in files {
+            for (condition, message) in self.conditions(path, watched) {
+                if self.fired.insert((path.clone(), condition)) {
+                    self.fire(path, &message);
+                }
+            }
+        }
+    }
+
+    // The conditions met by a file, with their message.
+    fn conditions(&mut self, path: &Path, watched: &WatchedFile) -> Vec<(&'static str, String)> {
+        let frames = watched.frame_count();
+        let new = || Checked { frames: 0, temperatures: (0.0, 0), grown: Instant::now() };
+        let checked = self.checked.entry(path.to_path_buf()).or_insert_with(new);
+        // The file has been rewritten (e.g. restarted run).
+        if frames < checked.frames {
+            *checked = new();
+        }
+        let mut met = Vec::new();
+        let column = |name: &str| watched.data.get(name).map(|v| &v[..frames]);
+        for name in ["Etot", "EPtot", "EKtot"] {
+            if let Some(frame) = column(name).and_then(|v| v[checked.frames..].iter().position(|e| e.is_nan())) {
+                met.push(("nan", format!("NaN {} at frame {}", name, checked.frames + frame + 1)));
+            }
+        }
+        if let (Some(spike), Some(temperatures)) = (self.args.alert_temp_spike, column("TEMP(K)")) {
+            // The missing temperatures (NaN) are left out, they would make the mean NaN for the rest of the watch.
+            for (i, t) in temperatures.iter().enumerate().skip(checked.frames).filter(|(_, t)| t.is_finite()) {
+                let (sum, count) = checked.temperatures;
+                if count > 0 && (t - sum / count as f64).abs() > spike {
+                    met.push(("temperature", format!("temperature of {} K at frame {}, {:.1} K from the mean of the \
+                        frames before it", t, i + 1, t - sum / count as f64)));
+                    break;
+                }
+                checked.temperatures = (sum + t, count + 1);
+            }
+        }
+        if let (Some(drift), Some(densities)) = (self.args.alert_density_drift, column("Density")) {
+            // The reference and the last density are taken from the frames that have one.
+            let densities: Vec<f64> = densities.iter().copied().filter(|d| d.is_finite()).collect();
+            if densities.len() > REFERENCE_FRAMES {
+                let reference = crate::analysis::mean(&densities[..REFERENCE_FRAMES]);
+                let last = densities[densities.len() - 1];
+                if ((last - reference) / reference).abs() > drift {
+                    met.push(("density", format!("density of {} g/cm^3, {:+.1}% from the first frames", last,
+                        100.0 * (last - reference) / reference)));
+                }
+            }
+        }
+        if frames > checked.frames {
+            checked.grown = Instant::now();
+        }
+        if let Some(stall) = self.args.alert_stall {
+            let idle = checked.grown.elapsed().as_secs_f64();
+            if !watched.ended() && idle > stall {
+                met.push(("stall", format!("no new frame for {:.0} s, the run may have stopped", idle)));
+            }
+        }
+        checked.frames = frames;
+        met
+    }
+
+    // Run the alert command and post to the webhook. A failed command or webhook is reported, it does not stop the watch.
+    fn fire(&self, path: &Path, message: &str) {
+        let message = format!("relis: {}: {}", path.display(), message);
+        println!("Alert: {}", message);
+        if let Some(cmd) = &self.args.alert_cmd {
+            let status = Command::new("sh")
+                .args(["-c", cmd])
+                .env("RELIS_ALERT", &message)
+                .env("RELIS_FILE", path)
+                .status();
+            match status {
+                Ok(status) if !status.success() => println!("The alert command failed ({})", status),
+                Err(e) => println!("The alert command failed: {}", e),
+                Ok(_) => {}
+            }
+        }
+        if let Some(url) = &self.args.alert_webhook {
+            let body = serde_json::json!({ "text": message }).to_string();
+            let status = Command::new("curl")
+                .args(["-fsS", "-X", "POST", "-H", "Content-Type: application/json", "--data", &body, url])
+                .status();
+            match status {
+                Ok(status) if !status.success() => println!("Posting the alert to {} failed ({})", url, status),
+                Err(e) => println!("Posting the alert to {} failed: {}", url, e),
+                Ok(_) => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_values_do_not_turn_the_alerts_off() {
+        let args = AlertArgs { alert_cmd: None, alert_webhook: None, alert_temp_spike: Some(50.0),
+            alert_density_drift: Some(0.05), alert_stall: None };
+        let mut alerts = Alerts::new(&args);
+        let mut watched = WatchedFile::default();
+        let mut densities = vec![f64::NAN];
+        densities.extend([1.0; REFERENCE_FRAMES]);
+        densities.extend([1.2, f64::NAN]);
+        let mut temperatures = vec![300.0, f64::NAN];
+        temperatures.extend(vec![300.0; densities.len() - 3]);
+        temperatures.push(400.0);
+        watched.data.insert("TEMP(K)".to_string(), temperatures);
+        watched.data.insert("Density".to_string(), densities);
+        let met: Vec<&str> = alerts.conditions(Path::new("md.out"), &watched).into_iter().map(|(c, _)| c).collect();
+        assert_eq!(met, ["temperature", "density"]);
+    }
+}
@@ -0,0 +1,53 @@
+// Parsing of sander and pmemd minimization outputs (imin=1): each printed step is a line of columns under
+// the "NSTEP ENERGY RMS GMAX" header, followed by the energy terms as in MD outputs.
+// The step printed again in the final results is left out.
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+use crate::RelisError;
+use regex::Regex;
+
+const COLUMNS: [&str; 4] = ["NSTEP", "ENERGY", "RMS", "GMAX"];
+
+#[derive(Default)]
+pub struct Reader {
+    // The previous line was the header of the columns.
+    step_header: bool,
+    last_step: Option<f64>,
+    // The current step has already been read.
+    repeated: bool,
+}
+
+impl Reader {
+    // Read a line of the results, the values of the steps are added to data, the energy terms being extracted with re
+    // (default if it is the default regex).
+    pub fn line(&mut self, line: &str, re: &Regex, default: bool, data: &mut BTreeMap<String, Vec<f64>>) -> Result<(), RelisError> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.starts_with(&COLUMNS) {
+            self.step_header = true;
+            return Ok(());
+        }
+        if self.step_header {
+            self.step_header = false;
+            let values = fields.iter().take(COLUMNS.len()).map(|f| f.parse::<f64>()).collect::<Result<Vec<f64>, _>>()?;
+            self.repeated = values.first() == self.last_step.as_ref();
+            if !self.repeated {
+                self.last_step = values.first().copied();
+                for (column, value) in COLUMNS.iter().zip(values) {
+                    data.entry(column.to_string()).or_default().push(value);
+                }
+            }
+            return Ok(());
+        }
+        if self.repeated {
+            return Ok(());
+        }
+        crate::read_fields(line, re, default, data)
+    }
+}
+
+// The minimizations are AMBER outputs with imin=1 in the echo of the input.
+pub fn detect(head: &str) -> bool {
+    static IMIN: OnceLock<Regex> = OnceLock::new();
+    let imin = IMIN.get_or_init(|| Regex::new(r"(?i)\bimin\s*=\s*1\b").unwrap());
+    crate::parser::detect_amber(head) && imin.is_match(head)
+}
@@ -0,0 +1,544 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use polars::prelude::*;
+use relis::time_column;
+use crate::manifest::ColumnCoverage;
+
+// Boltzmann constant in kcal/mol/K.
+pub const KB: f64 = 0.0019872041;
+
+// The name of a run (None for a table without run column) and its rows.
+pub type Run = (Option<String>, DataFrame);
+
+// The table of each run, or the whole table if it has no run column, with the name of the run.
+pub fn runs(df: &DataFrame) -> Result<Vec<Run>, Box<dyn Error>> {
+    let Ok(column) = df.column(crate::RUN_COLUMN) else {
+        return Ok(vec![(None, df.clone())]);
+    };
+    let column = column.utf8()?;
+    let mut names: Vec<String> = Vec::new();
+    for name in column.into_iter().flatten() {
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    }
+    let mut runs = Vec::new();
+    for name in names {
+        let mask = column.equal(name.as_str());
+        runs.push((Some(name), df.filter(&mask)?));
+    }
+    Ok(runs)
+}
+
+// The values of a column as floats, the missing ones (and NaN) left out.
+pub fn values(df: &DataFrame, name: &str) -> Result<Vec<f64>, Box<dyn Error>> {
+    Ok(pairs(df, name, name)?.into_iter().map(|(v, _)| v).collect())
+}
+
+// The values of two columns on the rows where both are known.
+pub fn pairs(df: &DataFrame, x: &str, y: &str) -> Result<Vec<(f64, f64)>, Box<dyn Error>> {
+    let x = df.column(x)?.cast(&DataType::Float64)?;
+    let y = df.column(y)?.cast(&DataType::Float64)?;
+    Ok(x.f64()?
+        .into_iter()
+        .zip(y.f64()?)
+        .filter_map(|(x, y)| Some((x?, y?)))
+        .filter(|(x, y)| x.is_finite() && y.is_finite())
+        .collect())
+}
+
+pub fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+// Slope of the least squares line through the points (x, y).
+pub fn slope(points: &[(f64, f64)]) -> f64 {
+    let x = mean(&points.iter().map(|p| p.0).collect::<Vec<_>>());
+    let y = mean(&points.iter().map(|p| p.1).collect::<Vec<_>>());
+    let covariance: f64 = points.iter().map(|p| (p.0 - x) * (p.1 - y)).sum();
+    let variance: f64 = points.iter().map(|p| (p.0 - x).powi(2)).sum();
+    covariance / variance
+}
+
+// Label of a run in the reports.
+fn label(run: &Option<String>) -> String {
+    run.as_ref().map_or(String::new(), |r| format!("[{}] ", r))
+}
+
+// Fit the drift of the total energy over time for NVE runs, in kcal/mol/ns and per degree of freedom.
+// The number of degrees of freedom is taken from the kinetic energy and the temperature: EKtot = Ndf kB T / 2.
+// Returns a warning for each run whose drift per degree of freedom exceeds the threshold (kcal/mol/ns).
+pub fn nve_report(df: &DataFrame, threshold: f64) -> Result<Vec<String>, Box<dyn Error>> {
+    let Some(time) = time_column(&df.get_column_names()) else {
+        println!("NVE report: no time column, the drift cannot be computed");
+        return Ok(Vec::new());
+    };
+    if !df.get_column_names().contains(&"Etot") {
+        println!("NVE report: no Etot column, the drift cannot be computed");
+        return Ok(Vec::new());
+    }
+    let mut warnings = Vec::new();
+    for (run, df) in runs(df)? {
+        let points = pairs(&df, &time, "Etot")?;
+        if points.len() < 2 {
+            println!("NVE report: {}not enough frames to fit the drift", label(&run));
+            continue;
+        }
+        // The time is in ps.
+        let drift = slope(&points) * 1000.0;
+        let dof = degrees_of_freedom(&df)?;
+        let per_dof = dof.map(|n| drift / n);
+        match per_dof {
+            Some(per_dof) => println!("NVE report: {}Etot drift {:.4} kcal/mol/ns, {:.3e} kcal/mol/ns per degree of freedom ({:.0} degrees of freedom)",
+                label(&run), drift, per_dof, dof.unwrap_or_default()),
+            None => println!("NVE report: {}Etot drift {:.4} kcal/mol/ns (no EKtot and TEMP(K) for the degrees of freedom)",
+                label(&run), drift),
+        }
+        // Without the degrees of freedom, the threshold is compared with the drift of the whole system.
+        let compared = per_dof.unwrap_or(drift);
+        if compared.abs() > threshold {
+            let warning = format!("NVE report: {}the energy drift ({:.3e} kcal/mol/ns{}) exceeds the threshold of {} kcal/mol/ns",
+                label(&run), compared, if per_dof.is_some() { " per degree of freedom" } else { "" }, threshold);
+            println!("{}", warning);
+            warnings.push(warning);
+        }
+    }
+    Ok(warnings)
+}
+
+// Mean number of degrees of freedom over the frames, from EKtot and TEMP(K).
+fn degrees_of_freedom(df: &DataFrame) -> Result<Option<f64>, Box<dyn Error>> {
+    let columns = df.get_column_names();
+    if !columns.contains(&"EKtot") || !columns.contains(&"TEMP(K)") {
+        return Ok(None);
+    }
+    let dof: Vec<f64> = pairs(df, "EKtot", "TEMP(K)")?
+        .into_iter()
+        .filter(|(_, t)| *t > 0.0)
+        .map(|(k, t)| 2.0 * k / (KB * t))
+        .collect();
+    Ok((!dof.is_empty()).then(|| mean(&dof)))
+}
+
+// Compare the mean TEMP(K) and PRESS of a file with the targets of its &cntrl namelist (temp0 and pres0), when
+// the thermostat (ntt > 0) or the barostat (ntp > 0) is on. Returns a warning for each deviation beyond the tolerance.
+pub fn target_check(file: &Path, data: &BTreeMap<String, Vec<f64>>, settings: &BTreeMap<String, f64>,
+    temp_tolerance: f64, press_tolerance: f64) -> Vec<String> {
+    let checks = [
+        ("TEMP(K)", "temp0", "ntt", temp_tolerance, "K"),
+        ("PRESS", "pres0", "ntp", press_tolerance, "bar"),
+    ];
+    let mut warnings = Vec::new();
+    for (column, setting, switch, tolerance, unit) in checks {
+        if !settings.get(switch).is_some_and(|s| *s > 0.0) {
+            continue;
+        }
+        let (Some(values), Some(target)) = (data.get(column), settings.get(setting)) else {
+            continue;
+        };
+        let values: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+        if values.is_empty() {
+            continue;
+        }
+        let mean = mean(&values);
+        if (mean - target).abs() > tolerance {
+            warnings.push(format!("{}: mean {} {:.2} {} deviates from {} = {} by more than {} {}",
+                file.display(), column, mean, unit, setting, target, tolerance, unit));
+        }
+    }
+    warnings
+}
+
+// The interval between two frames of a file in ps: ntpr * dt from its &cntrl namelist (AMBER files), or else the
+// median spacing of its time column. None if neither is known.
+pub fn output_interval(data: &BTreeMap<String, Vec<f64>>, settings: &BTreeMap<String, f64>) -> Option<f64> {
+    if let (Some(ntpr), Some(dt)) = (settings.get("ntpr"), settings.get("dt")) {
+        return Some(ntpr * dt);
+    }
+    let keys: Vec<&str> = data.keys().map(String::as_str).collect();
+    let times = &data[&time_column(&keys)?];
+    let mut spacings: Vec<f64> = times.windows(2).map(|w| w[1] - w[0]).filter(|s| s.is_finite()).collect();
+    spacings.sort_by(f64::total_cmp);
+    spacings.get(spacings.len() / 2).copied()
+}
+
+// Flag the files whose output interval differs from the most common one: pooling frames written at different
+// intervals gives more weight to the files written more often. Returns a warning for each of them.
+pub fn sampling_audit(files: &[(PathBuf, usize, Option<f64>)]) -> Vec<String> {
+    // Intervals are compared after rounding, the values being printed with few digits.
+    let key = |interval: f64| (interval * 1e6).round() as i64;
+    let mut counts: BTreeMap<i64, usize> = BTreeMap::new();
+    for interval in files.iter().filter_map(|f| f.2) {
+        *counts.entry(key(interval)).or_default() += 1;
+    }
+    let Some((common, _)) = counts.iter().max_by_key(|(_, count)| **count) else {
+        return Vec::new();
+    };
+    files
+        .iter()
+        .filter_map(|(path, _, interval)| Some((path, (*interval)?)))
+        .filter(|(_, interval)| key(*interval) != *common)
+        .map(|(path, interval)| format!("{}: frames every {} ps, the other files every {} ps", path.display(), interval,
+            *common as f64 / 1e6))
+        .collect()
+}
+
+// Track the density and the volume of NPT equilibrations: the time from which the running mean (over a window of
+// 5% of the frames) stays within a relative tolerance of the mean of the second half of the run, and the mean after
+// that time with the standard error of 5 block averages. Returns a warning for each quantity that does not stabilize.
+pub fn npt_report(df: &DataFrame, tolerance: f64) -> Result<Vec<String>, Box<dyn Error>> {
+    let Some(time) = time_column(&df.get_column_names()) else {
+        println!("NPT report: no time column, the convergence cannot be followed");
+        return Ok(Vec::new());
+    };
+    let mut warnings = Vec::new();
+    for (run, df) in runs(df)? {
+        for (column, unit) in [("Density", "g/cm^3"), ("VOLUME", "A^3")] {
+            if !df.get_column_names().contains(&column) {
+                continue;
+            }
+            let points = pairs(&df, &time, column)?;
+            match stabilization(&points, tolerance) {
+                Some((index, value, error)) => println!("NPT report: {}{} stable from {} = {} ps: {:.6} +/- {:.6} {}",
+                    label(&run), column, time, points[index].0, value, error, unit),
+                None => {
+                    let warning = format!("NPT report: {}{} does not stabilize within {}% of its final value",
+                        label(&run), column, tolerance * 100.0);
+                    println!("{}", warning);
+                    warnings.push(warning);
+                }
+            }
+        }
+    }
+    Ok(warnings)
+}
+
+// The first frame from which the running mean stays within the tolerance of the mean of the second half,
+// with the mean of the values after it and its standard error. None if there are too few frames.
+fn stabilization(points: &[(f64, f64)], tolerance: f64) -> Option<(usize, f64, f64)> {
+    if points.len() < 20 {
+        return None;
+    }
+    let values: Vec<f64> = points.iter().map(|p| p.1).collect();
+    let reference = mean(&values[values.len() / 2..]);
+    let window = (values.len() / 20).max(1);
+    let running: Vec<f64> = values.windows(window).map(mean).collect();
+    // The window ending at the last frame that is out of the tolerance: the run is stable after it.
+    let start = match running.iter().rposition(|m| (m - reference).abs() > tolerance * reference.abs()) {
+        Some(last) => last + window,
+        None => 0,
+    };
+    let stable = &values[start.min(values.len())..];
+    if stable.len() < 5 {
+        return None;
+    }
+    Some((start, mean(stable), block_error(stable, 5)))
+}
+
+// Standard error of the mean from the means of consecutive blocks, which accounts for the correlation of the frames.
+pub fn block_error(values: &[f64], blocks: usize) -> f64 {
+    let size = values.len() / blocks;
+    let means: Vec<f64> = values.chunks_exact(size).take(blocks).map(mean).collect();
+    let m = mean(&means);
+    let variance = means.iter().map(|x| (x - m).powi(2)).sum::<f64>() / (blocks - 1) as f64;
+    (variance / blocks as f64).sqrt()
+}
+
+// 95% confidence interval of the mean of correlated values, by moving-block bootstrap: the values are resampled
+// in blocks of n^(1/3) consecutive frames, which keep their correlation, and the 2.5% and 97.5% percentiles of the
+// means of the resamples are returned. The random generator has a fixed seed, so that runs can be reproduced.
+pub fn bootstrap_ci(values: &[f64], resamples: usize) -> Option<(f64, f64)> {
+    let n = values.len();
+    if n < 2 || resamples == 0 {
+        return None;
+    }
+    let block = ((n as f64).cbrt().ceil() as usize).clamp(1, n);
+    let starts = n - block + 1;
+    let mut rng = SplitMix64(0x5eed);
+    let mut means: Vec<f64> = (0..resamples)
+        .map(|_| {
+            let mut sum = 0.0;
+            let mut count = 0;
+            while count < n {
+                let start = (rng.next() % starts as u64) as usize;
+                for value in &values[start..(start + block).min(start + n - count)] {
+                    sum += value;
+                    count += 1;
+                }
+            }
+            sum / n as f64
+        })
+        .collect();
+    means.sort_by(f64::total_cmp);
+    let percentile = |p: f64| means[((p * (resamples - 1) as f64).round() as usize).min(resamples - 1)];
+    Some((percentile(0.025), percentile(0.975)))
+}
+
+// Mean over independent replicates of the mean of each one, with its standard error: the standard deviation of the
+// means of the replicates over the square root of their number. The replicates without values are left out.
+pub fn replicate_mean(replicates: &[Run], name: &str) -> Result<(f64, f64, usize), Box<dyn Error>> {
+    let mut means = Vec::new();
+    for (_, df) in replicates {
+        let values = values(df, name)?;
+        if !values.is_empty() {
+            means.push(mean(&values));
+        }
+    }
+    let n = means.len();
+    let m = mean(&means);
+    let variance = means.iter().map(|x| (x - m).powi(2)).sum::<f64>() / (n as f64 - 1.0);
+    Ok((m, (variance / n as f64).sqrt(), n))
+}
+
+// Statistics of each run of a ladder of temperatures (e.g. runs grouped by temp0), sorted by temperature: the mean
+// of each column with its standard error from 5 blocks, and the heat capacity from the fluctuations of the total
+// energy, Cv = (<Etot^2> - <Etot>^2) / (kB T^2) in kcal/mol/K, T being the mean temperature of the run.
+pub fn ladder(df: &DataFrame) -> Result<DataFrame, Box<dyn Error>> {
+    let time = time_column(&df.get_column_names());
+    let names: Vec<String> = df
+        .get_column_names()
+        .into_iter()
+        .filter(|c| *c != crate::RUN_COLUMN && Some(*c) != time.as_deref())
+        .map(String::from)
+        .collect();
+    let mut rows = Vec::new();
+    for (run, df) in runs(df)? {
+        let mut stats = Vec::new();
+        for name in &names {
+            let values = values(&df, name)?;
+            let error = if values.len() >= 10 { block_error(&values, 5) } else { f64::NAN };
+            stats.push((mean(&values), error));
+        }
+        let temperature = names.iter().position(|n| n == "TEMP(K)").map_or(f64::NAN, |i| stats[i].0);
+        let cv = match names.iter().position(|n| n == "Etot") {
+            Some(i) => {
+                let energies = values(&df, "Etot")?;
+                let variance = mean(&energies.iter().map(|e| (e - stats[i].0).powi(2)).collect::<Vec<_>>());
+                variance / (KB * temperature * temperature)
+            }
+            None => f64::NAN,
+        };
+        rows.push((run.unwrap_or_default(), df.height() as u32, temperature, stats, cv));
+    }
+    rows.sort_by(|a, b| a.2.total_cmp(&b.2));
+    // NaN are written as missing values.
+    let known = |v: f64| Some(v).filter(|v| v.is_finite());
+    let mut columns = vec![
+        Series::new(crate::RUN_COLUMN, rows.iter().map(|r| r.0.as_str()).collect::<Vec<_>>()),
+        Series::new("frames", rows.iter().map(|r| r.1).collect::<Vec<_>>()),
+    ];
+    for (i, name) in names.iter().enumerate() {
+        columns.push(Series::new(name, rows.iter().map(|r| known(r.3[i].0)).collect::<Vec<_>>()));
+        columns.push(Series::new(&format!("{}_err", name), rows.iter().map(|r| known(r.3[i].1)).collect::<Vec<_>>()));
+    }
+    columns.push(Series::new("Cv", rows.iter().map(|r| known(r.4)).collect::<Vec<_>>()));
+    Ok(DataFrame::new(columns)?)
+}
+
+// Reweighting of the frames of accelerated MD (aMD, GaMD) to the ensemble without boost, given with --reweight.
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum Reweight {
+    // Weights exp(dV/kT) of the frames: exact, but noisy when the boost varies much.
+    Exp,
+    // Cumulant expansion to the second order in dV/kT.
+    Cumulant2,
+    // Cumulant expansion to the third order in dV/kT.
+    Cumulant3,
+}
+
+// Mean of the values (first of the pairs) reweighted by the boost energy of their frame (second, kcal/mol)
+// at a temperature (K). With x = dV/kT, the mean <A exp(x)> / <exp(x)> is the derivative in t of ln <exp(x + tA)>
+// at t = 0, whose cumulant expansion gives <A> + cov(A, x) + <(A - <A>)(x - <x>)^2> / 2 + ...
+pub fn reweighted_mean(pairs: &[(f64, f64)], temperature: f64, method: Reweight) -> f64 {
+    let beta = 1.0 / (KB * temperature);
+    let a: Vec<f64> = pairs.iter().map(|p| p.0).collect();
+    let x: Vec<f64> = pairs.iter().map(|p| beta * p.1).collect();
+    let (mean_a, mean_x) = (mean(&a), mean(&x));
+    let moment = |power: i32| {
+        mean(&a.iter().zip(&x).map(|(a, x)| (a - mean_a) * (x - mean_x).powi(power)).collect::<Vec<_>>())
+    };
+    match method {
+        Reweight::Exp => {
+            // Shifted by the largest exponent, so that the weights do not overflow.
+            let max = x.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            let weights: Vec<f64> = x.iter().map(|x| (x - max).exp()).collect();
+            a.iter().zip(&weights).map(|(a, w)| a * w).sum::<f64>() / weights.iter().sum::<f64>()
+        }
+        Reweight::Cumulant2 => mean_a + moment(1),
+        Reweight::Cumulant3 => mean_a + moment(1) + moment(2) / 2.0,
+    }
+}
+
+// Where each column of the table (but the run and the time) has values: the fraction of the frames with a value, and
+// the first and last time with one, or number of the frame (from 1) if there is no time column. The files without
+// the column are left for the caller.
+pub fn coverage(df: &DataFrame, time: Option<&str>) -> Result<Vec<ColumnCoverage>, Box<dyn Error>> {
+    let times: Vec<Option<f64>> = match time {
+        Some(time) => df.column(time)?.cast(&DataType::Float64)?.f64()?.into_iter().collect(),
+        None => (1..=df.height()).map(|i| Some(i as f64)).collect(),
+    };
+    let mut coverage = Vec::new();
+    for column in df.get_columns().iter().filter(|c| c.name() != crate::RUN_COLUMN && Some(c.name()) != time) {
+        let values = column.cast(&DataType::Float64)?;
+        let known: Vec<f64> = values
+            .f64()?
+            .into_iter()
+            .zip(&times)
+            .filter(|(v, _)| v.is_some_and(f64::is_finite))
+            .filter_map(|(_, t)| *t)
+            .collect();
+        let count = values.f64()?.into_iter().filter(|v| v.is_some_and(f64::is_finite)).count();
+        coverage.push(ColumnCoverage {
+            column: column.name().to_string(),
+            fraction: count as f64 / df.height() as f64,
+            first: known.iter().copied().reduce(f64::min),
+            last: known.iter().copied().reduce(f64::max),
+            absent_from: Vec::new(),
+        });
+    }
+    Ok(coverage)
+}
+
+// Number of bins of the histogram of the boost energies for the anharmonicity, as in PyReweighting.
+const ANHARMONICITY_BINS: usize = 50;
+
+// Statistics of the boost energy dV (kcal/mol) of the frames of each run (GaMD window) on which the reweighting
+// relies: its mean, standard deviation and largest value, and its anharmonicity. The cumulant expansion to the second
+// order is accurate when the anharmonicity is small (below about 0.01).
+pub fn gamd_stats(df: &DataFrame, boost: &str) -> Result<DataFrame, Box<dyn Error>> {
+    let mut rows = Vec::new();
+    for (run, df) in runs(df)? {
+        let values = values(&df, boost)?;
+        let average = mean(&values);
+        let std = mean(&values.iter().map(|v| (v - average).powi(2)).collect::<Vec<_>>()).sqrt();
+        let max = values.iter().copied().fold(f64::NAN, f64::max);
+        rows.push((run, values.len() as u32, average, std, max, anharmonicity(&values)));
+    }
+    // NaN are written as missing values.
+    let known = |v: f64| Some(v).filter(|v| v.is_finite());
+    let mut columns = Vec::new();
+    if df.column(crate::RUN_COLUMN).is_ok() {
+        columns.push(Series::new(crate::RUN_COLUMN, rows.iter().map(|r| r.0.clone().unwrap_or_default()).collect::<Vec<_>>()));
+    }
+    columns.push(Series::new("frames", rows.iter().map(|r| r.1).collect::<Vec<_>>()));
+    columns.push(Series::new("dV_mean", rows.iter().map(|r| known(r.2)).collect::<Vec<_>>()));
+    columns.push(Series::new("dV_std", rows.iter().map(|r| known(r.3)).collect::<Vec<_>>()));
+    columns.push(Series::new("dV_max", rows.iter().map(|r| known(r.4)).collect::<Vec<_>>()));
+    columns.push(Series::new("anharmonicity", rows.iter().map(|r| known(r.5)).collect::<Vec<_>>()));
+    Ok(DataFrame::new(columns)?)
+}
+
+// Anharmonicity of the distribution of the values, computed as in PyReweighting: the entropy of the Gaussian of the
+// same variance, ln(2 pi e var) / 2, minus the entropy of the histogram of the values (integrated with the
+// trapezoidal rule). NaN if the values are all the same.
+fn anharmonicity(values: &[f64]) -> f64 {
+    let (min, max) = values.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| (min.min(*v), max.max(*v)));
+    if values.len() < 2 || max <= min {
+        return f64::NAN;
+    }
+    let width = (max - min) / ANHARMONICITY_BINS as f64;
+    let mut counts = [0usize; ANHARMONICITY_BINS];
+    for v in values {
+        counts[(((v - min) / width) as usize).min(ANHARMONICITY_BINS - 1)] += 1;
+    }
+    // The empty bins get a tiny density so that their logarithm is finite.
+    let terms: Vec<f64> = counts
+        .iter()
+        .map(|c| *c as f64 / (values.len() as f64 * width) + 1e-18)
+        .map(|p| p * p.ln())
+        .collect();
+    let entropy = -width * (terms.iter().sum::<f64>() - (terms[0] + terms[ANHARMONICITY_BINS - 1]) / 2.0);
+    let average = mean(values);
+    let variance = mean(&values.iter().map(|v| (v - average).powi(2)).collect::<Vec<_>>());
+    0.5 * (2.0 * std::f64::consts::PI * std::f64::consts::E * variance).ln() - entropy
+}
+
+// Small random number generator (SplitMix64), good enough for resampling.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+}
+
+// Forward and reverse cumulative means of each column over the frames of each run: the mean of the frames up to
+// each frame, and of the frames from each frame to the end. The two curves meet when the run has converged.
+// The frames are numbered in a "frame" column if there is no time column.
+pub fn convergence(df: &DataFrame, time: Option<&str>) -> Result<DataFrame, Box<dyn Error>> {
+    let mut table = DataFrame::new::<Series>(vec![])?;
+    for (run, df) in runs(df)? {
+        let (df, time) = match time {
+            Some(time) => (df, time),
+            None => (df.with_row_count(crate::FRAME_COLUMN, None)?, crate::FRAME_COLUMN),
+        };
+        let mut columns = Vec::new();
+        if let Some(run) = &run {
+            columns.push(Series::new(crate::RUN_COLUMN, vec![run.as_str(); df.height()]));
+        }
+        columns.push(df.column(time)?.clone());
+        for column in df.get_columns().iter().filter(|c| c.name() != time && c.name() != crate::RUN_COLUMN) {
+            let values: Vec<Option<f64>> = column.cast(&DataType::Float64)?.f64()?.into_iter().collect();
+            let mut reverse = cumulative_means(values.iter().rev());
+            reverse.reverse();
+            columns.push(Series::new(&format!("{}_forward", column.name()), cumulative_means(values.iter())));
+            columns.push(Series::new(&format!("{}_reverse", column.name()), reverse));
+        }
+        let run_table = DataFrame::new(columns)?;
+        table = if table.width() == 0 { run_table } else { table.vstack(&run_table)? };
+    }
+    Ok(table)
+}
+
+// Share of each energy term in the energy of each frame, in %: the terms are taken in absolute value, as most of
+// them are negative, so that the shares add up to 100 and can be stacked. The frames are numbered in a "frame"
+// column if there is no time column. None if the table has less than two of the terms.
+pub fn decomposition(df: &DataFrame, time: Option<&str>) -> Result<Option<DataFrame>, Box<dyn Error>> {
+    let terms: Vec<&str> = crate::fields::ENERGY_TERMS.iter().copied().filter(|t| df.column(t).is_ok()).collect();
+    if terms.len() < 2 {
+        return Ok(None);
+    }
+    let (df, time) = match time {
+        Some(time) => (df.clone(), time),
+        None => (df.with_row_count(crate::FRAME_COLUMN, None)?, crate::FRAME_COLUMN),
+    };
+    let mut values = Vec::new();
+    for term in &terms {
+        let column: Vec<Option<f64>> = df.column(term)?.cast(&DataType::Float64)?.f64()?.into_iter().collect();
+        values.push(column);
+    }
+    let totals: Vec<f64> = (0..df.height()).map(|row| values.iter().filter_map(|v| v[row]).map(f64::abs).sum()).collect();
+    let mut columns = Vec::new();
+    if let Ok(run) = df.column(crate::RUN_COLUMN) {
+        columns.push(run.clone());
+    }
+    columns.push(df.column(time)?.clone());
+    for (term, values) in terms.iter().zip(values) {
+        let shares: Vec<Option<f64>> = values
+            .iter()
+            .zip(&totals)
+            .map(|(value, total)| value.filter(|_| *total > 0.0).map(|v| 100.0 * v.abs() / total))
+            .collect();
+        columns.push(Series::new(term, shares));
+    }
+    Ok(Some(DataFrame::new(columns)?))
+}
+
+// The mean of the values up to each one, the missing values being left out.
+fn cumulative_means<'a>(values: impl Iterator<Item = &'a Option<f64>>) -> Vec<Option<f64>> {
+    let (mut sum, mut count) = (0.0, 0);
+    values
+        .map(|value| {
+            if let Some(value) = value.filter(|v| v.is_finite()) {
+                sum += value;
+                count += 1;
+            }
+            (count > 0).then(|| sum / count as f64)
+        })
+        .collect()
+}
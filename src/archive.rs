@@ -0,0 +1,249 @@
+use std::cell::RefCell;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use flate2::read::MultiGzDecoder;
+use relis::RelisError;
+
+// Files inside tar archives (.tar, .tar.gz or .tgz), given as "run.tar.gz::prod*.lis", read without extracting them.
+// The members are listed in the order of the archive and a compressed archive can only be read from its beginning,
+// so the stream of the archive is kept between two members: reading the members in order reads the archive once.
+
+const BLOCK: u64 = 512;
+
+// Separator between the path of the archive and the pattern or name of the member.
+const SEPARATOR: &str = "::";
+
+thread_local! {
+    // The archive being read, positioned at the header of the member following the last one read.
+    static STREAM: RefCell<Option<Stream>> = const { RefCell::new(None) };
+}
+
+struct Stream {
+    archive: PathBuf,
+    reader: Box<dyn Read>,
+    // Position in the (uncompressed) archive.
+    pos: u64,
+}
+
+struct Entry {
+    name: String,
+    size: u64,
+    is_file: bool,
+}
+
+// Return true if the path or pattern is a member of an archive.
+pub fn is_archive(path: &str) -> bool {
+    path.contains(SEPARATOR)
+}
+
+// Split "archive::member" into the path of the archive and the member.
+pub fn split(path: &str) -> Result<(PathBuf, &str), RelisError> {
+    let (archive, member) = path.split_once(SEPARATOR).ok_or_else(|| RelisError::Glob {
+        pattern: path.to_string(),
+        message: "not a member of an archive".to_string(),
+    })?;
+    Ok((PathBuf::from(archive), member))
+}
+
+// List the files of the archive matching the pattern, "archive::pattern", in the order of the archive.
+// A pattern without '/' is matched against the names of the files, whatever their directory in the archive.
+pub fn list(pattern: &str) -> Result<Vec<PathBuf>, RelisError> {
+    let (archive, member_pattern) = split(pattern)?;
+    // The members are always named with '/', even in archives written on Windows.
+    let member_pattern = if cfg!(windows) { member_pattern.replace('\\', "/") } else { member_pattern.to_string() };
+    let member_pattern = member_pattern.as_str();
+    let glob = glob::Pattern::new(member_pattern)
+        .map_err(|e| RelisError::Glob { pattern: member_pattern.to_string(), message: e.to_string() })?;
+    let mut stream = Stream::open(&archive)?;
+    let mut files = Vec::new();
+    while let Some(entry) = stream.next_entry()? {
+        let name = if member_pattern.contains('/') { entry.name.as_str() } else { file_name(&entry.name) };
+        if entry.is_file && glob.matches(name) {
+            files.push(PathBuf::from(format!("{}{}{}", archive.display(), SEPARATOR, entry.name)));
+        }
+        stream.skip(padded(entry.size))?;
+    }
+    Ok(files)
+}
+
+// Open a member of an archive, "archive::member".
+pub fn open(path: &Path) -> Result<Member, RelisError> {
+    let path = path
+        .to_str()
+        .ok_or_else(|| RelisError::io(path, std::io::ErrorKind::InvalidInput, "archive path is not valid UTF-8"))?;
+    let (archive, name) = split(path)?;
+    let cached = STREAM.with(|s| s.borrow_mut().take()).filter(|s| s.archive == archive);
+    let mut stream = match cached {
+        Some(stream) => stream,
+        None => Stream::open(&archive)?,
+    };
+    // Look for the member after the current position, then from the beginning of the archive.
+    for restarted in [false, true] {
+        if restarted {
+            if stream.pos == 0 {
+                break;
+            }
+            stream = Stream::open(&archive)?;
+        }
+        while let Some(entry) = stream.next_entry()? {
+            if entry.is_file && entry.name == name {
+                return Ok(Member { stream: Some(stream), remaining: entry.size, padding: padded(entry.size) - entry.size });
+            }
+            stream.skip(padded(entry.size))?;
+        }
+    }
+    Err(RelisError::io(&archive, std::io::ErrorKind::NotFound, format!("no member {}", name)))
+}
+
+// A member of an archive being read. When dropped, the stream of the archive is kept to read the next members.
+pub struct Member {
+    stream: Option<Stream>,
+    remaining: u64,
+    padding: u64,
+}
+
+impl Read for Member {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let Some(stream) = &mut self.stream else {
+            return Ok(0);
+        };
+        let max = buf.len().min(self.remaining as usize);
+        let n = stream.reader.read(&mut buf[..max])?;
+        if n == 0 && max > 0 {
+            return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "truncated archive"));
+        }
+        stream.pos += n as u64;
+        self.remaining -= n as u64;
+        Ok(n)
+    }
+}
+
+impl Drop for Member {
+    fn drop(&mut self) {
+        if let Some(mut stream) = self.stream.take() {
+            // The stream is only kept if it could be moved to the next header.
+            if stream.skip(self.remaining + self.padding).is_ok() {
+                STREAM.with(|s| *s.borrow_mut() = Some(stream));
+            }
+        }
+    }
+}
+
+impl Stream {
+    fn open(archive: &Path) -> Result<Stream, RelisError> {
+        let file = std::fs::File::open(archive).map_err(|e| RelisError::from(e).in_file(archive))?;
+        let name = archive.to_string_lossy();
+        let reader: Box<dyn Read> = if name.ends_with(".gz") || name.ends_with(".tgz") {
+            Box::new(MultiGzDecoder::new(std::io::BufReader::new(file)))
+        } else if name.ends_with(".tar") {
+            Box::new(std::io::BufReader::new(file))
+        } else {
+            return Err(RelisError::io(archive, std::io::ErrorKind::InvalidInput,
+                "unsupported archive, expected .tar, .tar.gz or .tgz"));
+        };
+        Ok(Stream { archive: archive.to_path_buf(), reader, pos: 0 })
+    }
+
+    fn skip(&mut self, n: u64) -> Result<(), RelisError> {
+        let skipped = std::io::copy(&mut (&mut self.reader).take(n), &mut std::io::sink())
+            .map_err(|e| RelisError::from(e).in_file(&self.archive))?;
+        self.pos += skipped;
+        if skipped < n {
+            return Err(RelisError::io(&self.archive, std::io::ErrorKind::UnexpectedEof, "truncated archive"));
+        }
+        Ok(())
+    }
+
+    fn read_block(&mut self) -> Result<Option<[u8; BLOCK as usize]>, RelisError> {
+        let mut block = [0; BLOCK as usize];
+        match self.reader.read_exact(&mut block) {
+            Ok(()) => {}
+            // Some archives end without the blocks of zeros.
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e.into()),
+        }
+        self.pos += BLOCK;
+        Ok(Some(block))
+    }
+
+    // Read the header of the next member, the stream is then at the beginning of its data.
+    fn next_entry(&mut self) -> Result<Option<Entry>, RelisError> {
+        self.read_entry().map_err(|e| e.in_file(&self.archive))
+    }
+
+    // The long names of the GNU and pax formats are taken from the extra headers preceding the member.
+    fn read_entry(&mut self) -> Result<Option<Entry>, RelisError> {
+        let mut long_name = None;
+        loop {
+            let Some(header) = self.read_block()? else {
+                return Ok(None);
+            };
+            // The archive ends with blocks of zeros.
+            if header.iter().all(|b| *b == 0) {
+                return Ok(None);
+            }
+            let size = octal(&header[124..136])?;
+            let kind = header[156];
+            match kind {
+                // GNU long name: the data is the name of the next member.
+                b'L' => {
+                    let data = self.read_data(size)?;
+                    long_name = Some(text(&data));
+                }
+                // pax extended header: "length key=value\n" records.
+                b'x' => {
+                    let data = self.read_data(size)?;
+                    let records = String::from_utf8_lossy(&data).into_owned();
+                    for record in records.lines() {
+                        if let Some((_, path)) = record.split_once(" path=") {
+                            long_name = Some(path.to_string());
+                        }
+                    }
+                }
+                _ => {
+                    let name = long_name.take().unwrap_or_else(|| {
+                        let (name, prefix) = (text(&header[0..100]), text(&header[345..500]));
+                        // The ustar format splits long names into a prefix and a name.
+                        if &header[257..262] == b"ustar" && !prefix.is_empty() { format!("{}/{}", prefix, name) } else { name }
+                    });
+                    let name = name.trim_start_matches("./").to_string();
+                    return Ok(Some(Entry { name, size, is_file: kind == b'0' || kind == 0 }));
+                }
+            }
+        }
+    }
+
+    // Read the data of a member with its padding.
+    fn read_data(&mut self, size: u64) -> Result<Vec<u8>, RelisError> {
+        let mut data = Vec::new();
+        (&mut self.reader).take(size).read_to_end(&mut data)?;
+        self.pos += data.len() as u64;
+        self.skip(padded(size) - size)?;
+        Ok(data)
+    }
+}
+
+// Size rounded up to a multiple of the size of the blocks.
+fn padded(size: u64) -> u64 {
+    size.div_ceil(BLOCK) * BLOCK
+}
+
+// Parse a number of the header, written in octal and ended by a NUL or a space.
+fn octal(field: &[u8]) -> Result<u64, RelisError> {
+    let digits = text(field);
+    let digits = digits.trim();
+    if digits.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(digits, 8).map_err(|_| RelisError::parse(format!("invalid size in a tar header: \"{}\"", digits)))
+}
+
+// A string of the header, ended by a NUL.
+fn text(field: &[u8]) -> String {
+    let end = field.iter().position(|b| *b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+fn file_name(name: &str) -> &str {
+    name.rsplit('/').next().unwrap_or(name)
+}
@@ -0,0 +1,90 @@
+use std::error::Error;
+use std::io::Read;
+use std::time::{Duration, Instant};
+use clap::Args;
+use polars::prelude::*;
+use relis::{to_dataframe, ByteLines};
+use crate::InputArgs;
+
+#[derive(Args)]
+pub struct BenchArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    /// Number of times the files are processed, the fastest time of each stage is reported.
+    #[arg(long, default_value_t = 3)]
+    repeat: usize,
+}
+
+// Time taken by each stage of the processing of the files.
+#[derive(Clone, Copy)]
+struct Timings {
+    io: Duration,
+    parse: Duration,
+    table: Duration,
+    write: Duration,
+}
+
+// Process the files as relis does, without the outputs, and print the time taken by each stage: reading the bytes
+// of the files, parsing their lines, assembling the table and writing it as CSV (in the directory of the files).
+pub fn bench(args: &BenchArgs) -> Result<(), Box<dyn Error>> {
+    let (dir, _, files) = crate::find_files(&args.input)?;
+    let scratch = dir.join(".relis-bench.csv");
+    let mut best: Option<Timings> = None;
+    let mut bytes = 0;
+    let mut frames = 0;
+    for run in 1..=args.repeat.max(1) {
+        let mut timings = Timings { io: Duration::ZERO, parse: Duration::ZERO, table: Duration::ZERO, write: Duration::ZERO };
+        let mut df = DataFrame::default();
+        bytes = 0;
+        for file in &files {
+            let start = Instant::now();
+            let crate::OpenedFile { mut reader, .. } = args.input.open(file)?;
+            let mut content = Vec::new();
+            reader.read_to_end(&mut content)?;
+            timings.io += start.elapsed();
+            bytes += content.len();
+
+            let start = Instant::now();
+            let engine = args.input.engine(&content[..content.len().min(relis::DETECTION_BYTES)]);
+            let file_data = crate::read_lines(engine, &args.input, ByteLines::new(&content).map(Ok))?;
+            timings.parse += start.elapsed();
+
+            let start = Instant::now();
+            if !file_data.data.is_empty() {
+                df = crate::stack(&df, to_dataframe(&file_data.data)?)?;
+            }
+            timings.table += start.elapsed();
+        }
+        let start = Instant::now();
+        let mut df = if df.is_empty() { df } else { crate::build_table(df, &args.input)? };
+        timings.table += start.elapsed();
+        frames = df.height();
+
+        let start = Instant::now();
+        CsvWriter::new(std::fs::File::create(&scratch)?).finish(&mut df)?;
+        timings.write += start.elapsed();
+        std::fs::remove_file(&scratch)?;
+
+        println!("Run {}: {:.3} s", run, (timings.io + timings.parse + timings.table + timings.write).as_secs_f64());
+        best = Some(match best {
+            Some(b) => Timings {
+                io: b.io.min(timings.io),
+                parse: b.parse.min(timings.parse),
+                table: b.table.min(timings.table),
+                write: b.write.min(timings.write),
+            },
+            None => timings,
+        });
+    }
+    let Some(best) = best else {
+        return Ok(());
+    };
+    let megabytes = bytes as f64 / 1e6;
+    println!("{} files, {:.1} MB, {} frames (fastest of {} runs):", files.len(), megabytes, frames, args.repeat.max(1));
+    for (stage, time) in [("IO", best.io), ("parsing", best.parse), ("table", best.table), ("CSV writing", best.write)] {
+        println!("    {:<12} {:>8.3} s  {:>8.1} MB/s", stage, time.as_secs_f64(), megabytes / time.as_secs_f64());
+    }
+    let total = (best.io + best.parse + best.table + best.write).as_secs_f64();
+    println!("    {:<12} {:>8.3} s  {:>8.1} MB/s  {:.1} files/s", "total", total, megabytes / total, files.len() as f64 / total);
+    Ok(())
+}
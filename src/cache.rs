@@ -0,0 +1,160 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use serde::{Deserialize, Serialize};
+use relis::Engine;
+use crate::FileData;
+
+const CHECKPOINT: &str = "checkpoint.json";
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// Cache of the values read from local files, in a .relis-cache directory with one JSON file per file read.
+// An entry is used if the file has kept its size and modification time, and if it was read with the same options
+// by the same version of relis (the parsers change from one version to the next).
+// A checkpoint file records the run in progress, so that an interrupted run can be told apart from a finished one.
+pub struct Cache {
+    dir: PathBuf,
+}
+
+// The run in progress, removed when all the files have been read.
+#[derive(Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub pattern: String,
+    pub files: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    // Version of relis that read the file, the entries written before it was recorded are never used.
+    #[serde(default)]
+    version: String,
+    path: PathBuf,
+    size: u64,
+    // Modification time, in ns since the Unix epoch.
+    modified: u128,
+    options: String,
+    engine: Option<String>,
+    // NaN are written as null in JSON.
+    data: BTreeMap<String, Vec<Option<f64>>>,
+    warnings: Vec<String>,
+    #[serde(default)]
+    settings: BTreeMap<String, f64>,
+    #[serde(default)]
+    columns: Vec<String>,
+}
+
+impl Cache {
+    pub fn new(dir: &Path) -> Result<Cache, Box<dyn Error>> {
+        let dir = dir.join(".relis-cache");
+        std::fs::create_dir_all(&dir)?;
+        Ok(Cache { dir })
+    }
+
+    // The values of the file if they are in the cache and still valid.
+    pub fn get(&self, file: &Path, options: &str) -> Option<FileData> {
+        let (path, size, modified) = key(file)?;
+        let entry: Entry = serde_json::from_slice(&std::fs::read(self.entry_path(&path)).ok()?).ok()?;
+        if entry.version != VERSION || entry.path != path || entry.size != size || entry.modified != modified || entry.options != options {
+            return None;
+        }
+        let data = entry.data
+            .into_iter()
+            .map(|(key, values)| (key, values.into_iter().map(|v| v.unwrap_or(f64::NAN)).collect()))
+            .collect();
+        Some(FileData { engine: entry.engine.as_deref().and_then(Engine::from_name), data, warnings: entry.warnings,
+            settings: entry.settings, bytes: 0, invalid_lines: 0, columns: entry.columns })
+    }
+
+    // Keep the values of a file, nothing is done for remote files and members of archives.
+    pub fn put(&self, file: &Path, options: &str, file_data: &FileData) -> Result<(), Box<dyn Error>> {
+        let Some((path, size, modified)) = key(file) else {
+            return Ok(());
+        };
+        let data = file_data.data
+            .iter()
+            .map(|(key, values)| (key.clone(), values.iter().map(|v| Some(*v).filter(|v| !v.is_nan())).collect()))
+            .collect();
+        let entry = Entry {
+            version: VERSION.to_string(),
+            path: path.clone(),
+            size,
+            modified,
+            options: options.to_string(),
+            engine: file_data.engine.map(|e| e.name().to_string()),
+            data,
+            warnings: file_data.warnings.clone(),
+            settings: file_data.settings.clone(),
+            columns: file_data.columns.clone(),
+        };
+        write(&self.entry_path(&path), &serde_json::to_vec(&entry)?)
+    }
+
+    // The run that was interrupted before reading all its files, if any.
+    pub fn checkpoint(&self) -> Option<Checkpoint> {
+        serde_json::from_slice(&std::fs::read(self.dir.join(CHECKPOINT)).ok()?).ok()
+    }
+
+    pub fn start_run(&self, pattern: &str, files: usize) -> Result<(), Box<dyn Error>> {
+        let checkpoint = Checkpoint { pattern: pattern.to_string(), files };
+        write(&self.dir.join(CHECKPOINT), &serde_json::to_vec(&checkpoint)?)
+    }
+
+    pub fn finish_run(&self) -> Result<(), Box<dyn Error>> {
+        std::fs::remove_file(self.dir.join(CHECKPOINT))?;
+        Ok(())
+    }
+
+    // The entries are named after a hash of the path, the path is checked when reading them.
+    fn entry_path(&self, path: &Path) -> PathBuf {
+        let mut hasher = DefaultHasher::new();
+        path.hash(&mut hasher);
+        self.dir.join(format!("{:016x}.json", hasher.finish()))
+    }
+}
+
+// Write the file through a temporary file, so that a run killed while writing does not leave a truncated file.
+fn write(path: &Path, contents: &[u8]) -> Result<(), Box<dyn Error>> {
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, contents)?;
+    std::fs::rename(tmp, path)?;
+    Ok(())
+}
+
+// The absolute path, size and modification time of a local file, None if it is not a local file.
+fn key(file: &Path) -> Option<(PathBuf, u64, u128)> {
+    let name = file.to_string_lossy();
+    if crate::remote::is_remote(&name) || crate::archive::is_archive(&name) {
+        return None;
+    }
+    let metadata = std::fs::metadata(file).ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?.as_nanos();
+    Some((std::fs::canonicalize(file).ok()?, metadata.len(), modified))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_of_other_versions_are_not_used() {
+        let dir = std::env::temp_dir().join(format!("relis-cache-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("md.out");
+        std::fs::write(&file, " NSTEP =        1   TIME(PS) =       0.002\n").unwrap();
+        let cache = Cache::new(&dir).unwrap();
+        let file_data = FileData { engine: None, data: BTreeMap::from([("NSTEP".to_string(), vec![1.0])]),
+            warnings: Vec::new(), settings: BTreeMap::new(), bytes: 0, invalid_lines: 0, columns: Vec::new() };
+        cache.put(&file, "", &file_data).unwrap();
+        assert_eq!(cache.get(&file, "").unwrap().data["NSTEP"], vec![1.0]);
+
+        let entry_path = cache.entry_path(&std::fs::canonicalize(&file).unwrap());
+        let mut entry: serde_json::Value = serde_json::from_slice(&std::fs::read(&entry_path).unwrap()).unwrap();
+        entry["version"] = "0.0.0".into();
+        std::fs::write(&entry_path, entry.to_string()).unwrap();
+        assert!(cache.get(&file, "").is_none());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
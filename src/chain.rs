@@ -0,0 +1,85 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use relis::time_column;
+
+// The first and last values of the time (or of NSTEP) in a segment of a chained simulation.
+pub struct Segment {
+    path: PathBuf,
+    column: String,
+    first: f64,
+    last: f64,
+    // Spacing of the last two frames, None if the segment has a single frame.
+    interval: Option<f64>,
+}
+
+impl Segment {
+    // The segment of a file, from its time column or NSTEP. None if the file has neither.
+    pub fn new(path: &Path, data: &BTreeMap<String, Vec<f64>>) -> Option<Segment> {
+        let keys: Vec<&str> = data.keys().map(String::as_str).collect();
+        let column = time_column(&keys).or_else(|| data.contains_key("NSTEP").then(|| "NSTEP".to_string()))?;
+        let values = &data[&column];
+        let (first, last) = (*values.first()?, *values.last()?);
+        let interval = values.len().checked_sub(2).map(|i| last - values[i]);
+        Some(Segment { path: path.to_path_buf(), column, first, last, interval })
+    }
+}
+
+// Check that the segments of each directory, in natural order (prod2 before prod10), follow each other:
+// the first frame of a segment must come one output interval after the last frame of the previous one.
+// Returns a message for each broken link, e.g. when a segment was started from the wrong restart file.
+pub fn check(mut segments: Vec<Segment>) -> Vec<String> {
+    segments.sort_by(|a, b| natural_cmp(&a.path.to_string_lossy(), &b.path.to_string_lossy()));
+    let mut broken = Vec::new();
+    for pair in segments.windows(2) {
+        let (previous, next) = (&pair[0], &pair[1]);
+        if previous.path.parent() != next.path.parent() || previous.column != next.column {
+            continue;
+        }
+        let gap = next.first - previous.last;
+        // Half an interval of slack for the rounding of the printed values.
+        let continues = match previous.interval {
+            Some(interval) if interval > 0.0 => gap > 0.0 && (gap - interval).abs() <= interval / 2.0,
+            _ => gap > 0.0,
+        };
+        if !continues {
+            broken.push(format!("{} starts at {} = {} but {} ends at {} = {}", next.path.display(), next.column,
+                next.first, previous.path.display(), previous.column, previous.last));
+        }
+    }
+    broken
+}
+
+// Compare two names with their numbers compared by value, so that "prod2" comes before "prod10".
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a, b);
+    loop {
+        match (a.chars().next(), b.chars().next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let (number_a, rest_a) = split_number(a);
+                let (number_b, rest_b) = split_number(b);
+                // Leading zeros are left out, a longer number is larger.
+                let (number_a, number_b) = (number_a.trim_start_matches('0'), number_b.trim_start_matches('0'));
+                let order = number_a.len().cmp(&number_b.len()).then_with(|| number_a.cmp(number_b));
+                if order != Ordering::Equal {
+                    return order;
+                }
+                (a, b) = (rest_a, rest_b);
+            }
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(&y);
+                }
+                (a, b) = (&a[x.len_utf8()..], &b[y.len_utf8()..]);
+            }
+        }
+    }
+}
+
+// Split the digits at the beginning of a string from the rest.
+fn split_number(s: &str) -> (&str, &str) {
+    s.split_at(s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len()))
+}
@@ -0,0 +1,207 @@
+use polars::prelude::*;
+use relis::RelisError;
+
+// A column computed from the other columns, e.g. EDIFF = Etot - EPtot - EKtot.
+#[derive(Clone)]
+pub struct Derived {
+    pub name: String,
+    pub expression: String,
+}
+
+// Parse a "NAME = EXPRESSION" definition given on the command line.
+pub fn parse_derived(s: &str) -> Result<Derived, String> {
+    match s.split_once('=') {
+        Some((name, expression)) if !name.trim().is_empty() && !expression.trim().is_empty() => {
+            Ok(Derived { name: name.trim().to_string(), expression: expression.trim().to_string() })
+        }
+        _ => Err(format!("expected NAME = EXPRESSION, got \"{}\"", s)),
+    }
+}
+
+// Add the derived columns to the table, in the order given: a column can use the ones defined before it.
+pub fn add_columns(df: DataFrame, derived: &[Derived]) -> Result<DataFrame, RelisError> {
+    // Nothing has been read yet (e.g. when watching).
+    if derived.is_empty() || df.is_empty() {
+        return Ok(df);
+    }
+    let mut columns: Vec<String> = df.get_column_names().iter().map(|c| c.to_string()).collect();
+    let mut lazy = df.lazy();
+    for d in derived {
+        if columns.contains(&d.name) {
+            return Err(RelisError::schema(format!("cannot derive \"{}\": the table already has this column", d.name)));
+        }
+        let expr = Parser::new(&d.expression, &columns)
+            .parse()
+            .map_err(|message| RelisError::parse(format!("in the expression of \"{}\": {}", d.name, message)))?;
+        lazy = lazy.with_column(expr.alias(&d.name));
+        columns.push(d.name.clone());
+    }
+    Ok(lazy.collect()?)
+}
+
+// Length of the number at the beginning of s: digits and a point, then an exponent with its sign (1e-3, 2.5E+3).
+fn number_len(s: &str) -> usize {
+    let mantissa = s.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(s.len());
+    let rest = &s[mantissa..];
+    let Some(exponent) = rest.strip_prefix(['e', 'E']) else {
+        return mantissa;
+    };
+    let sign = exponent.starts_with(['+', '-']) as usize;
+    let digits = exponent[sign..].find(|c: char| !c.is_ascii_digit()).unwrap_or(exponent.len() - sign);
+    // An "e" not followed by digits is left to the rest of the expression.
+    if digits == 0 {
+        mantissa
+    } else {
+        mantissa + 1 + sign + digits
+    }
+}
+
+// Parser of arithmetic expressions over the columns: numbers, column names, + - * /, unary minus and parentheses.
+// The names are those of the table (the longest one matching is taken, so that TEMP(K) or 1-4 NB can be written
+// as they are), or any name between double quotes.
+struct Parser<'a> {
+    rest: &'a str,
+    columns: &'a [String],
+}
+
+impl<'a> Parser<'a> {
+    fn new(expression: &'a str, columns: &'a [String]) -> Parser<'a> {
+        Parser { rest: expression, columns }
+    }
+
+    fn parse(mut self) -> Result<Expr, String> {
+        let expr = self.sum()?;
+        match self.peek() {
+            None => Ok(expr),
+            Some(c) => Err(format!("unexpected '{}'", c)),
+        }
+    }
+
+    // sum = product (('+' | '-') product)*
+    fn sum(&mut self) -> Result<Expr, String> {
+        let mut expr = self.product()?;
+        while let Some(op) = self.peek().filter(|c| *c == '+' || *c == '-') {
+            self.advance(1);
+            let right = self.product()?;
+            expr = if op == '+' { expr + right } else { expr - right };
+        }
+        Ok(expr)
+    }
+
+    // product = factor (('*' | '/') factor)*
+    fn product(&mut self) -> Result<Expr, String> {
+        let mut expr = self.factor()?;
+        while let Some(op) = self.peek().filter(|c| *c == '*' || *c == '/') {
+            self.advance(1);
+            let right = self.factor()?;
+            expr = if op == '*' { expr * right } else { expr / right };
+        }
+        Ok(expr)
+    }
+
+    // factor = '-' factor | '(' sum ')' | column | number
+    fn factor(&mut self) -> Result<Expr, String> {
+        if let Some(name) = self.column() {
+            return Ok(col(&name));
+        }
+        match self.peek() {
+            Some('-') => {
+                self.advance(1);
+                Ok(lit(0.0) - self.factor()?)
+            }
+            Some('(') => {
+                self.advance(1);
+                let expr = self.sum()?;
+                if self.peek() != Some(')') {
+                    return Err("missing ')'".to_string());
+                }
+                self.advance(1);
+                Ok(expr)
+            }
+            Some('"') => {
+                let end = self.rest[1..].find('"').ok_or("missing closing '\"'")?;
+                let name = self.rest[1..=end].to_string();
+                self.advance(end + 2);
+                if !self.columns.contains(&name) {
+                    return Err(format!("no column \"{}\"", name));
+                }
+                Ok(col(&name))
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let len = number_len(self.rest);
+                let number = &self.rest[..len];
+                let value: f64 = number.parse().map_err(|_| format!("invalid number \"{}\"", number))?;
+                self.advance(len);
+                Ok(lit(value))
+            }
+            Some(c) => {
+                let word: String = self.rest.chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect();
+                Err(format!("no column \"{}\"", if word.is_empty() { c.to_string() } else { word }))
+            }
+            None => Err("unexpected end of the expression".to_string()),
+        }
+    }
+
+    // The longest name of a column at the current position, taken if it is not followed by more of a name.
+    fn column(&mut self) -> Option<String> {
+        self.peek()?;
+        let name = self
+            .columns
+            .iter()
+            .filter(|c| self.rest.starts_with(c.as_str()))
+            .filter(|c| !self.rest[c.len()..].starts_with(|n: char| n.is_alphanumeric() || n == '_'))
+            .max_by_key(|c| c.len())?
+            .clone();
+        self.advance(name.len());
+        Some(name)
+    }
+
+    // The next character that is not a space.
+    fn peek(&mut self) -> Option<char> {
+        self.rest = self.rest.trim_start();
+        self.rest.chars().next()
+    }
+
+    fn advance(&mut self, bytes: usize) {
+        self.rest = &self.rest[bytes..];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> DataFrame {
+        df!("Etot" => [10.0, 20.0], "EPtot" => [4.0, 8.0], "TEMP(K)" => [300.0, 310.0]).unwrap()
+    }
+
+    // The values of a derived column computed over table().
+    fn derive(expression: &str) -> Result<Vec<f64>, RelisError> {
+        let derived = parse_derived(&format!("X = {}", expression)).unwrap();
+        let df = add_columns(table(), &[derived])?;
+        Ok(df.column("X").unwrap().f64().unwrap().into_no_null_iter().collect())
+    }
+
+    #[test]
+    fn signed_exponents() {
+        assert_eq!(derive("Etot * 1e-3").unwrap(), vec![0.01, 0.02]);
+        assert_eq!(derive("EPtot * 2.5E+3").unwrap(), vec![10000.0, 20000.0]);
+        assert_eq!(derive("Etot * 1E2").unwrap(), vec![1000.0, 2000.0]);
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(derive("Etot - EPtot * 2").unwrap(), vec![2.0, 4.0]);
+        assert_eq!(derive("(Etot - EPtot) * 2").unwrap(), vec![12.0, 24.0]);
+        assert_eq!(derive("-EPtot / 2 + \"TEMP(K)\"").unwrap(), vec![298.0, 306.0]);
+        assert_eq!(derive("TEMP(K) - 300").unwrap(), vec![0.0, 10.0]);
+    }
+
+    #[test]
+    fn unknown_columns() {
+        let message = derive("Etot - EKtot").unwrap_err().to_string();
+        assert!(message.contains("no column \"EKtot\""), "{}", message);
+        let message = derive("\"1-4 NB\" + 1").unwrap_err().to_string();
+        assert!(message.contains("no column \"1-4 NB\""), "{}", message);
+    }
+}
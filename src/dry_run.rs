@@ -0,0 +1,98 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::io::BufRead;
+use relis::{Engine, Lines, Section};
+use crate::InputArgs;
+
+// List the files matching the pattern, with the fields found in their first frame and their number of frames.
+// Only the first frame is parsed, the frames are counted from the lines starting with the field marking the frames
+// (NSTEP for MD outputs), as when the values are extracted.
+pub fn dry_run(input: &InputArgs) -> Result<(), Box<dyn Error>> {
+    let (_, _, files) = crate::find_files(input)?;
+    let mut total = 0;
+    for file in &files {
+        let crate::OpenedFile { engine, reader } = input.open(file)?;
+        let (frames, fields) = scan_file(reader, input, engine.unwrap_or_default())?;
+        println!("{}: {} frames ({})\n    fields: {}", file.display(), frames,
+            engine.map_or("format not recognized", |e| e.name()), fields.join(", "));
+        total += frames;
+    }
+    println!("Total: {} frames in {} files, nothing written (dry run).", total, files.len());
+    Ok(())
+}
+
+// Return the number of frames of the file and the fields of its first frame, in the order they are printed.
+fn scan_file(reader: impl BufRead, input: &InputArgs, engine: Engine) -> Result<(usize, Vec<String>), Box<dyn Error>> {
+    // The other formats name their columns once, all the values are read.
+    if engine != Engine::Amber {
+        let mut extractor = input.extractor(engine);
+        let mut data = BTreeMap::new();
+        for line in Lines::new(reader) {
+            extractor.line(&line?, &mut data)?;
+        }
+        let frames = data.values().map(Vec::len).max().unwrap_or(0);
+        return Ok((frames, data.into_keys().collect()));
+    }
+    let mut section = Section::default();
+    let mut fields: Vec<String> = Vec::new();
+    let mut marker: Option<String> = None;
+    let default = relis::is_default_regex(&input.regex);
+    let mut first_frame_read = false;
+    let mut frames = 0;
+    for line in Lines::new(reader) {
+        let line = line?;
+        if !section.keep(&line, &input.start_pattern, &input.end_pattern) {
+            if section.ended {
+                break;
+            }
+            continue;
+        }
+        if relis::skip_line(&line, default) {
+            continue;
+        }
+        // The first frame ends when a field is seen again. Its lines are read as they are when extracting the
+        // values, so that the fields have the names of the columns written (EELEC_2, "1-4 NB").
+        if !first_frame_read {
+            let mut line_data = BTreeMap::new();
+            relis::read_fields(&line, &input.regex, default, &mut line_data)?;
+            if marker.is_none() && !line_data.is_empty() {
+                marker = Some(relis::frame_marker(&line, &line_data));
+            }
+            if line_data.keys().any(|key| fields.contains(key)) {
+                first_frame_read = true;
+            } else {
+                crate::record_columns(&line, &line_data, &mut fields);
+            }
+        }
+        if marker.as_ref().is_some_and(|marker| line.trim_start().starts_with(marker.as_str())) {
+            frames += 1;
+        }
+    }
+    Ok((frames, fields))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        input: InputArgs,
+    }
+
+    #[test]
+    fn fields_are_named_as_the_columns() {
+        let input = Cli::parse_from(["relis", "*.out"]).input;
+        let frame = " NSTEP =      500   TIME(PS) =       1.000  TEMP(K) =   300.12  PRESS =     0.0\n \
+                     1-4 NB =       5.0000  1-4 EEL =      50.0000  VDWAALS =     -10.0000\n \
+                     EELEC  =     -100.0000  EHBOND  =        0.0000  EELEC  =       -1.0000\n";
+        let header = "| Local SIZE OF NONBOND LIST =    1234567\n| TOTAL SIZE OF NONBOND LIST =    1234567\n";
+        let text = format!("   4.  RESULTS\n{}{}{}   A V E R A G E S\n", header, frame, frame.replace("500", "1000"));
+        let (frames, fields) = scan_file(text.as_bytes(), &input, Engine::Amber).unwrap();
+        assert_eq!(frames, 2);
+        assert_eq!(fields, ["NSTEP", "TIME(PS)", "TEMP(K)", "PRESS", "1-4 NB", "1-4 EEL", "VDWAALS", "EELEC", "EHBOND",
+            "EELEC_2"]);
+    }
+}
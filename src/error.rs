@@ -0,0 +1,102 @@
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+// The errors of the search and the reading of the files: the file and the line where they happened are given
+// when they are known, so that the faulty file can be found (and skipped) among thousands of files.
+#[derive(Debug, Error)]
+pub enum RelisError {
+    // A file that cannot be opened or read, local, remote or in an archive.
+    #[error("{}{source}", location(.path, 0))]
+    Io {
+        path: Option<PathBuf>,
+        #[source]
+        source: std::io::Error,
+    },
+    // A pattern of files that cannot be used.
+    #[error("invalid pattern \"{pattern}\": {message}")]
+    Glob { pattern: String, message: String },
+    #[error("invalid regex: {0}")]
+    Regex(#[from] regex::Error),
+    // A line that cannot be read, line is 0 when it is not known.
+    #[error("{}{message}", location(.path, *.line))]
+    Parse {
+        path: Option<PathBuf>,
+        line: usize,
+        message: String,
+    },
+    // Tables that do not fit together, e.g. missing columns.
+    #[error("{}{message}", location(.path, 0))]
+    Schema { path: Option<PathBuf>, message: String },
+    #[cfg(feature = "dataframe")]
+    #[error(transparent)]
+    DataFrame(#[from] polars::error::PolarsError),
+}
+
+impl RelisError {
+    pub fn parse(message: impl Into<String>) -> RelisError {
+        RelisError::Parse { path: None, line: 0, message: message.into() }
+    }
+
+    pub fn schema(message: impl Into<String>) -> RelisError {
+        RelisError::Schema { path: None, message: message.into() }
+    }
+
+    // An IO error with a message, e.g. failing external commands or unsupported archives.
+    pub fn io(path: &Path, kind: std::io::ErrorKind, message: impl Into<String>) -> RelisError {
+        RelisError::Io { path: Some(path.to_path_buf()), source: std::io::Error::new(kind, message.into()) }
+    }
+
+    // Set the file in which the error happened, if it is not known yet.
+    pub fn in_file(mut self, file: &Path) -> RelisError {
+        if let RelisError::Io { path, .. } | RelisError::Parse { path, .. } | RelisError::Schema { path, .. } = &mut self {
+            path.get_or_insert_with(|| file.to_path_buf());
+        }
+        self
+    }
+
+    // Set the line at which a parse error happened, if it is not known yet.
+    pub fn at_line(mut self, number: usize) -> RelisError {
+        if let RelisError::Parse { line, .. } = &mut self {
+            if *line == 0 {
+                *line = number;
+            }
+        }
+        self
+    }
+
+    // The file in which the error happened, if it is known.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            RelisError::Io { path, .. } | RelisError::Parse { path, .. } | RelisError::Schema { path, .. } => path.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for RelisError {
+    fn from(source: std::io::Error) -> RelisError {
+        RelisError::Io { path: None, source }
+    }
+}
+
+impl From<std::num::ParseFloatError> for RelisError {
+    fn from(e: std::num::ParseFloatError) -> RelisError {
+        RelisError::parse(format!("invalid value: {}", e))
+    }
+}
+
+// "file:line: " prefix of the messages, with what is known.
+fn location(path: &Option<PathBuf>, line: usize) -> String {
+    let mut location = String::new();
+    if let Some(path) = path {
+        write!(location, "{}", path.display()).unwrap();
+        if line > 0 {
+            write!(location, ":{}", line).unwrap();
+        }
+        location.push_str(": ");
+    } else if line > 0 {
+        write!(location, "line {}: ", line).unwrap();
+    }
+    location
+}
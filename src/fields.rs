@@ -0,0 +1,142 @@
+use std::ffi::OsStr;
+use clap::builder::{PossibleValue, StringValueParser, TypedValueParser};
+
+// Names of the fields printed by sander and pmemd in the RESULTS section.
+pub const KNOWN_FIELDS: &[&str] = &[
+    "NSTEP", "TIME(PS)", "TEMP(K)", "PRESS", "Etot", "EKtot", "EPtot", "BOND", "ANGLE", "DIHED",
+    "1-4 NB", "1-4 EEL", "VDWAALS", "EELEC", "EHBOND", "RESTRAINT", "EAMBER", "EKCMT", "VIRIAL",
+    "VOLUME", "Density", "DV/DL",
+    // Self-guided Langevin dynamics.
+    "SGLF", "SGHF", "SGMS", "TEMPSG", "TEMPLF", "TEMPHF", "EPOTLF", "EPOTHF", "SGWT",
+];
+
+// The terms of EPtot, in the order they are printed.
+pub const ENERGY_TERMS: &[&str] = &["BOND", "ANGLE", "DIHED", "1-4 NB", "1-4 EEL", "VDWAALS", "EELEC", "EHBOND", "RESTRAINT"];
+
+// Sets of fields selected with --preset.
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum Preset {
+    // Thermodynamic state: TEMP(K), PRESS, Density, VOLUME.
+    Thermo,
+    // Energy terms: Etot, EPtot, EKtot and the terms of EPtot.
+    Energy,
+    // Thermodynamic integration: DV/DL and EPtot.
+    Ti,
+}
+
+impl Preset {
+    pub fn fields(&self) -> &'static [&'static str] {
+        match self {
+            Preset::Thermo => &["TEMP(K)", "PRESS", "Density", "VOLUME"],
+            Preset::Energy => &["Etot", "EPtot", "EKtot", "BOND", "ANGLE", "DIHED", "1-4 NB", "1-4 EEL", "VDWAALS", "EELEC",
+                "EHBOND", "RESTRAINT"],
+            Preset::Ti => &["DV/DL", "EPtot"],
+        }
+    }
+}
+
+// Order of the columns after the run and the time, given with --column-order.
+#[derive(Clone, Copy, Default, clap::ValueEnum)]
+pub enum ColumnOrder {
+    // Order in which the fields first appear in the files (Etot, EKtot, EPtot, BOND, ...).
+    Input,
+    // Alphabetical order of the names.
+    #[default]
+    Alpha,
+    // Order of the fields given with --fields and --preset, or the order in which AMBER prints them, the other fields
+    // after them in alphabetical order.
+    Preset,
+}
+
+// Meaning of the fields read from the AMBER files, listed by `relis fields` and written in the NetCDF files.
+pub const DESCRIPTIONS: &[(&str, &str)] = &[
+    ("NSTEP", "Number of MD steps done"),
+    ("TIME(PS)", "Simulated time"),
+    ("TEMP(K)", "Temperature, from the kinetic energy"),
+    ("PRESS", "Pressure"),
+    ("Etot", "Total energy, EPtot + EKtot"),
+    ("EKtot", "Kinetic energy"),
+    ("EPtot", "Potential energy, the sum of the energy terms"),
+    ("BOND", "Bond stretching energy"),
+    ("ANGLE", "Angle bending energy"),
+    ("DIHED", "Dihedral (torsion) energy"),
+    ("1-4 NB", "Van der Waals energy of the atoms 3 bonds apart (1-4 pairs), scaled by scnb"),
+    ("1-4 EEL", "Electrostatic energy of the atoms 3 bonds apart (1-4 pairs), scaled by scee"),
+    ("VDWAALS", "Van der Waals energy of the other pairs of atoms"),
+    ("EELEC", "Electrostatic energy of the other pairs of atoms, with the reciprocal space part of PME"),
+    ("EHBOND", "Hydrogen bond energy (10-12 potential), 0 with the current force fields"),
+    ("RESTRAINT", "Energy of the restraints (positional and NMR)"),
+    ("EAMBER", "Potential energy without the restraints"),
+    ("EKCMT", "Kinetic energy of the centers of mass of the molecules, used for the pressure"),
+    ("VIRIAL", "Virial of the centers of mass of the molecules, used for the pressure"),
+    ("VOLUME", "Volume of the periodic box"),
+    ("Density", "Density of the system"),
+    ("DV/DL", "Derivative of the potential energy with respect to lambda (thermodynamic integration)"),
+    ("SGLF", "Guiding factor of the low frequency motions (SGLD)"),
+    ("SGHF", "Guiding factor of the high frequency motions (SGLD)"),
+    ("SGMS", "Momentum scaling of the guiding force (SGLD)"),
+    ("TEMPSG", "Guiding temperature (SGLD)"),
+    ("TEMPLF", "Temperature of the low frequency motions (SGLD)"),
+    ("TEMPHF", "Temperature of the high frequency motions (SGLD)"),
+    ("EPOTLF", "Potential energy of the low frequency motions (SGLD)"),
+    ("EPOTHF", "Potential energy of the high frequency motions (SGLD)"),
+    ("SGWT", "Weighting factor for reweighting to the canonical ensemble (SGLD)"),
+    ("QMMM_ESCF", "Energy of the QM region (QM/MM)"),
+    ("QMMM_DFTBESCF", "Energy of the QM region with DFTB (QM/MM)"),
+    ("NMR_Bond", "Energy of the NMR distance restraints"),
+    ("NMR_Angle", "Energy of the NMR angle restraints"),
+    ("NMR_Torsion", "Energy of the NMR torsion restraints"),
+    ("GAMD_BOOST", "Boost potential of the frame, the sum of the boost energies of gamd.log (GaMD)"),
+];
+
+// Meaning of a known field.
+pub fn description(field: &str) -> Option<&'static str> {
+    DESCRIPTIONS.iter().find(|(name, _)| *name == field).map(|(_, description)| *description)
+}
+
+// Print the known fields with their unit and their meaning.
+pub fn print_fields() {
+    for (name, description) in DESCRIPTIONS {
+        let name = match unit(name) {
+            Some(unit) => format!("{} ({})", name, unit),
+            None => name.to_string(),
+        };
+        println!("{:<30} {}", name, description);
+    }
+}
+
+// Unit of a known field, written in a way understood by the udunits library (used by NetCDF readers).
+// The energies of the files of the other engines are in kcal/mol as well: those in kJ/mol (GROMACS, OpenMM) are all
+// converted, renamed or not (e.g. GROMACS "Coulomb (SR)", "LJ (SR)"), so that a table never mixes energy units.
+pub fn unit(field: &str) -> Option<&'static str> {
+    match field {
+        "TIME(PS)" => Some("ps"),
+        "TEMP(K)" | "TEMPSG" | "TEMPLF" | "TEMPHF" => Some("K"),
+        "PRESS" => Some("bar"),
+        "VOLUME" => Some("angstrom^3"),
+        "Density" => Some("g/cm^3"),
+        "EKCMT" | "VIRIAL" | "Etot" | "EKtot" | "EPtot" | "BOND" | "ANGLE" | "DIHED" | "1-4 NB"
+        | "1-4 EEL" | "VDWAALS" | "EELEC" | "EHBOND" | "RESTRAINT" | "EAMBER" | "DV/DL"
+        | "EPOTLF" | "EPOTHF" | "QMMM_ESCF" | "QMMM_DFTBESCF"
+        | "NMR_Bond" | "NMR_Angle" | "NMR_Torsion" | "GAMD_BOOST" => Some("kcal/mol"),
+        _ => None,
+    }
+}
+
+// Value parser for the arguments taking a field name.
+// Any name is accepted, the known ones are offered by the shell completion.
+#[derive(Clone)]
+pub struct FieldParser;
+
+impl TypedValueParser for FieldParser {
+    type Value = String;
+
+    fn parse_ref(&self, cmd: &clap::Command, arg: Option<&clap::Arg>, value: &OsStr) -> Result<String, clap::Error> {
+        StringValueParser::new().parse_ref(cmd, arg, value)
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        // Names with a space would be completed as two words.
+        Some(Box::new(KNOWN_FIELDS.iter().filter(|f| !f.contains(' ')).map(|f| PossibleValue::new(*f))))
+    }
+}
@@ -0,0 +1,104 @@
+// Parsing of the gamd.log files written by pmemd with GaMD (igamd > 0): a header line starting with '#' naming the
+// columns (ntwx, total_nstep, the unboosted energies, the force weights and the boost energies), then one line of
+// values per frame. The total boost of each frame, the sum of the boost energies, is added in GAMD_BOOST.
+use std::collections::BTreeMap;
+use crate::parser::Parser;
+use crate::RelisError;
+
+// Column of the total boost energy (kcal/mol), added to the boost energies of the file.
+pub const BOOST_COLUMN: &str = "GAMD_BOOST";
+
+// AMBER name of the gamd.log columns that have one.
+const AMBER_NAMES: [(&str, &str); 3] = [
+    ("total_nstep", "NSTEP"),
+    ("Unboosted-Potential-Energy", "EPtot"),
+    ("Unboosted-Dihedral-Energy", "DIHED"),
+];
+
+#[derive(Default)]
+pub struct Reader {
+    // Names of the columns, from the header.
+    names: Vec<String>,
+}
+
+impl Reader {
+    // Read a line of the log, the values of the frames are added to data.
+    pub fn line(&mut self, line: &str, data: &mut BTreeMap<String, Vec<f64>>) -> Result<(), RelisError> {
+        let line = line.trim();
+        if let Some(header) = line.strip_prefix('#') {
+            // The other comment lines are sentences ("All energy terms are stored in unit of kcal/mol").
+            if header.contains("nstep") {
+                self.names = header
+                    .split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|n| !n.is_empty())
+                    .map(amber_name)
+                    .collect();
+            }
+            return Ok(());
+        }
+        if line.is_empty() {
+            return Ok(());
+        }
+        if self.names.is_empty() {
+            return Err(RelisError::parse("values found before the header naming the columns"));
+        }
+        let values = line.split_whitespace().map(|v| v.parse::<f64>()).collect::<Result<Vec<f64>, _>>()?;
+        if values.len() != self.names.len() {
+            return Err(RelisError::parse(format!("{} values for the {} columns of the header", values.len(), self.names.len())));
+        }
+        let mut boost = 0.0;
+        for (name, value) in self.names.iter().zip(values) {
+            if name.starts_with("Boost-Energy") {
+                boost += value;
+            }
+            data.entry(name.clone()).or_default().push(value);
+        }
+        data.entry(BOOST_COLUMN.to_string()).or_default().push(boost);
+        Ok(())
+    }
+}
+
+impl Parser for Reader {
+    fn read_line(&mut self, line: &str, data: &mut BTreeMap<String, Vec<f64>>) -> Result<bool, RelisError> {
+        self.line(line, data)?;
+        Ok(true)
+    }
+}
+
+// gamd.log files start with their title, or at least have the boost energies in their header.
+pub fn detect(head: &str) -> bool {
+    head.contains("Gaussian accelerated Molecular Dynamics log") || head.contains("Boost-Energy-Potential")
+}
+
+fn amber_name(name: &str) -> String {
+    AMBER_NAMES
+        .iter()
+        .find(|(gamd, _)| *gamd == name)
+        .map_or(name, |(_, amber)| amber)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boost_energies_are_added_up() {
+        let log = "# Gaussian accelerated Molecular Dynamics log file\n\
+                   # All energy terms are stored in unit of kcal/mol\n\
+                   # ntwx,total_nstep,Unboosted-Potential-Energy,Unboosted-Dihedral-Energy,Total-Force-Weight,\
+                   Dihedral-Force-Weight,Boost-Energy-Potential,Boost-Energy-Dihedral\n\
+                   \x20      500       500   -1000.0   200.0   1.0   0.5   3.0   1.5\n\
+                   \x20      500      1000   -1001.0   201.0   1.0   0.5   2.0   0.5\n";
+        assert!(detect(log));
+        let mut reader = Reader::default();
+        let mut data = BTreeMap::new();
+        for line in log.lines() {
+            reader.line(line, &mut data).unwrap();
+        }
+        assert_eq!(data["NSTEP"], vec![500.0, 1000.0]);
+        assert_eq!(data["EPtot"], vec![-1000.0, -1001.0]);
+        assert_eq!(data["DIHED"], vec![200.0, 201.0]);
+        assert_eq!(data[BOOST_COLUMN], vec![4.5, 2.5]);
+    }
+}
@@ -0,0 +1,141 @@
+use std::error::Error;
+use std::fmt::Write as _;
+use std::path::Path;
+
+// How the values are laid out in the CSV file.
+pub enum Layout<'a> {
+    // One column per field, with the name of the time column if any (it may have been renamed).
+    Wide { time: Option<&'a str> },
+    // The columns (time, variable, value), the time column may be the frame number.
+    Long { time: &'a str },
+    // The forward and reverse cumulative means of each field ("<field>_forward", "<field>_reverse"), plotted together.
+    Convergence { time: &'a str },
+    // The shares of the energy terms, stacked in a single plot.
+    Stacked { time: &'a str },
+}
+
+// Write a gnuplot script that plots every column of the CSV against TIME(PS), one PNG per column.
+// The images are written next to the CSV file, named after the column.
+// 1st arg: Path of the script to create.
+// 2nd arg: Path of the CSV file containing the data.
+// 3rd arg: The names of the fields (the columns of the wide table).
+// 4th arg: The layout of the CSV file.
+// 5th arg: The separator of the columns of the CSV file.
+pub fn write_script(script_path: &Path, csv_path: &Path, columns: &[&str], layout: Layout, delimiter: u8) -> Result<(), Box<dyn Error>> {
+    let dir = csv_path.parent().unwrap_or_else(|| Path::new(""));
+    let time = match layout {
+        Layout::Wide { time } => time,
+        Layout::Long { time } | Layout::Convergence { time } | Layout::Stacked { time } => Some(time),
+    };
+    let convergence = matches!(layout, Layout::Convergence { .. });
+    let mut script = String::new();
+    writeln!(script, "# Generated by relis, run with: gnuplot {}", script_path.display())?;
+    if delimiter == b'\t' {
+        writeln!(script, "set datafile separator tab")?;
+    } else {
+        writeln!(script, "set datafile separator \"{}\"", quote(&(delimiter as char).to_string()))?;
+    }
+    writeln!(script, "set terminal pngcairo size 1200,800 noenhanced")?;
+    writeln!(script, "set grid")?;
+    if let Layout::Stacked { time } = layout {
+        write_stacked(&mut script, dir, csv_path, columns, time)?;
+        std::fs::write(script_path, script)?;
+        return Ok(());
+    }
+    if convergence {
+        writeln!(script, "set key")?;
+    } else {
+        writeln!(script, "unset key")?;
+    }
+    if time.is_some_and(|t| t != crate::FRAME_COLUMN) {
+        writeln!(script, "set xlabel \"Time (ps)\"")?;
+    } else {
+        writeln!(script, "set xlabel \"Frame\"")?;
+    }
+    for col in columns.iter().filter(|c| Some(**c) != time) {
+        let png_path = if convergence {
+            dir.join(format!("plot_convergence_{}.png", file_stem(col)))
+        } else {
+            dir.join(format!("plot_{}.png", file_stem(col)))
+        };
+        writeln!(script)?;
+        writeln!(script, "set output \"{}\"", quote(&png_path.display().to_string()))?;
+        writeln!(script, "set title \"{}\"", quote(col))?;
+        writeln!(script, "set ylabel \"{}\"", quote(&label(col)))?;
+        // Column 0 is the pseudo-column holding the line number in gnuplot.
+        let x = match time {
+            Some(time) => format!("\"{}\"", quote(time)),
+            None => "0".to_string(),
+        };
+        let csv = quote(&csv_path.display().to_string());
+        // In the long layout, the rows of the other fields are left out as NaN.
+        let y = match layout {
+            Layout::Wide { .. } => format!("\"{}\"", quote(col)),
+            Layout::Long { .. } => format!("(strcol(\"variable\") eq \"{}\" ? column(\"value\") : NaN)", quote(col)),
+            Layout::Stacked { .. } => unreachable!(),
+            Layout::Convergence { .. } => {
+                writeln!(script, "plot \"{}\" using {}:\"{}_forward\" with lines title \"forward\", \\", csv, x, quote(col))?;
+                writeln!(script, "     \"{}\" using {}:\"{}_reverse\" with lines title \"reverse\"", csv, x, quote(col))?;
+                continue;
+            }
+        };
+        writeln!(script, "plot \"{}\" using {}:{} with lines", csv, x, y)?;
+    }
+    writeln!(script, "\nunset output")?;
+    std::fs::write(script_path, script)?;
+    Ok(())
+}
+
+// Plot the columns stacked on top of each other against the time, in plot_decomposition.png: each area goes up to
+// the sum of its column and of the ones before it, the highest being drawn first so that the others are seen.
+fn write_stacked(script: &mut String, dir: &Path, csv_path: &Path, columns: &[&str], time: &str) -> Result<(), Box<dyn Error>> {
+    let columns: Vec<&str> = columns.iter().copied().filter(|c| *c != time).collect();
+    writeln!(script, "set key outside right")?;
+    if time != crate::FRAME_COLUMN {
+        writeln!(script, "set xlabel \"Time (ps)\"")?;
+    } else {
+        writeln!(script, "set xlabel \"Frame\"")?;
+    }
+    writeln!(script, "set ylabel \"Share of the energy (%)\"")?;
+    writeln!(script, "set yrange [0:100]")?;
+    writeln!(script, "set style fill solid 0.8 noborder")?;
+    writeln!(script, "set output \"{}\"", quote(&dir.join("plot_decomposition.png").display().to_string()))?;
+    let csv = quote(&csv_path.display().to_string());
+    let plots: Vec<String> = (0..columns.len())
+        .rev()
+        .map(|i| {
+            let sum: Vec<String> = columns[..=i].iter().map(|c| format!("column(\"{}\")", quote(c))).collect();
+            format!("\"{}\" using \"{}\":({}) with filledcurves x1 title \"{}\"", csv, quote(time), sum.join("+"),
+                quote(columns[i]))
+        })
+        .collect();
+    writeln!(script, "plot {}", plots.join(", \\\n     "))?;
+    writeln!(script, "\nunset output")?;
+    Ok(())
+}
+
+// Return an axis label for an AMBER field, with its unit when it is known.
+fn label(col: &str) -> String {
+    match col {
+        "TEMP(K)" => "Temperature (K)".to_string(),
+        "NSTEP" => "Step".to_string(),
+        _ => match crate::fields::unit(col) {
+            Some(unit) => format!("{} ({})", col, unit),
+            None => col.to_string(),
+        },
+    }
+}
+
+// Turn a column name into something usable in a file name, e.g. "TEMP(K)" -> "TEMP_K".
+fn file_stem(col: &str) -> String {
+    let stem: String = col
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    stem.trim_matches('_').to_string()
+}
+
+// Escape a string to be used between double quotes in a gnuplot script.
+fn quote(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
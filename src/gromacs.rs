@@ -0,0 +1,170 @@
+// Parsing of GROMACS md.log files: each energy block is a "Step Time" header, then the "Energies" tables,
+// lines of names alternating with lines of values, in columns of 15 characters. The reading stops at the averages.
+// The fields having an AMBER equivalent are renamed and converted to AMBER units. The other energies are converted
+// to kcal/mol too, so that all the energies of a table have one unit, and the other fields keep their GROMACS unit.
+use std::collections::BTreeMap;
+use crate::parser::Parser;
+use crate::RelisError;
+
+const COLUMN_WIDTH: usize = 15;
+const KJ_PER_KCAL: f64 = 4.184;
+
+// AMBER name of the GROMACS fields that have one, with the factor converting the value to AMBER units.
+const AMBER_NAMES: [(&str, &str, f64); 9] = [
+    ("Bond", "BOND", 1.0 / KJ_PER_KCAL),
+    ("Angle", "ANGLE", 1.0 / KJ_PER_KCAL),
+    ("Potential", "EPtot", 1.0 / KJ_PER_KCAL),
+    ("Kinetic En.", "EKtot", 1.0 / KJ_PER_KCAL),
+    ("Total Energy", "Etot", 1.0 / KJ_PER_KCAL),
+    ("Temperature", "TEMP(K)", 1.0),
+    ("Pressure (bar)", "PRESS", 1.0),
+    // nm^3 to A^3.
+    ("Volume", "VOLUME", 1000.0),
+    // kg/m^3 to g/cm^3.
+    ("Density", "Density", 0.001),
+];
+
+// Beginning of the names of the fields of the energy tables that are not energies (kJ/mol): temperatures, pressures,
+// box dimensions and the constraint deviation.
+const NOT_ENERGIES: [&str; 9] = ["Temperature", "T-", "Pres", "Vir-", "Box-", "Volume", "Density", "Constr. rmsd", "#Surf"];
+
+// Where the reading of the log is.
+#[derive(Default)]
+pub struct Reader {
+    // The previous line was the "Step Time" header.
+    step_header: bool,
+    // In the tables of an energy block.
+    in_energies: bool,
+    // Names read from the previous line of the tables, waiting for their values.
+    names: Option<Vec<String>>,
+    // The averages have been reached.
+    pub ended: bool,
+}
+
+impl Reader {
+    // Read a line of the log, the values of the energy blocks are added to data.
+    pub fn line(&mut self, line: &str, data: &mut BTreeMap<String, Vec<f64>>) -> Result<(), RelisError> {
+        if self.ended {
+            return Ok(());
+        }
+        if line.contains("A V E R A G E S") {
+            self.ended = true;
+            return Ok(());
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if self.step_header {
+            self.step_header = false;
+            if let [step, time] = fields[..] {
+                data.entry("NSTEP".to_string()).or_default().push(step.parse()?);
+                data.entry("TIME(PS)".to_string()).or_default().push(time.parse()?);
+            }
+            return Ok(());
+        }
+        if fields == ["Step", "Time"] {
+            self.step_header = true;
+            return Ok(());
+        }
+        if line.trim_start().starts_with("Energies (") {
+            self.in_energies = true;
+            return Ok(());
+        }
+        if !self.in_energies {
+            return Ok(());
+        }
+        if fields.is_empty() {
+            self.in_energies = false;
+            self.names = None;
+            return Ok(());
+        }
+        match self.names.take() {
+            None => self.names = Some(names(line)),
+            Some(names) => {
+                for (name, value) in names.iter().zip(fields) {
+                    let (name, factor) = amber_name(name);
+                    data.entry(name).or_default().push(value.parse::<f64>()? * factor);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Parser for Reader {
+    fn read_line(&mut self, line: &str, data: &mut BTreeMap<String, Vec<f64>>) -> Result<bool, RelisError> {
+        self.line(line, data)?;
+        Ok(!self.ended)
+    }
+
+    fn ended(&self) -> bool {
+        self.ended
+    }
+}
+
+// md.log files name GROMACS in their header, the older ones only have the energy tables.
+pub fn detect(head: &str) -> bool {
+    head.contains("GROMACS") || head.contains("Energies (kJ/mol)")
+}
+
+// Split a line of names, each name being right aligned in a column (names may contain spaces).
+fn names(line: &str) -> Vec<String> {
+    let chars: Vec<char> = line.chars().collect();
+    chars
+        .chunks(COLUMN_WIDTH)
+        .map(|chunk| chunk.iter().collect::<String>().trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+fn amber_name(name: &str) -> (String, f64) {
+    AMBER_NAMES
+        .iter()
+        .find(|(gromacs, _, _)| *gromacs == name)
+        .map_or_else(|| (name.to_string(), energy_factor(name)), |(_, amber, factor)| (amber.to_string(), *factor))
+}
+
+// Factor converting a field without AMBER equivalent to kcal/mol if it is an energy, 1 for the other fields.
+fn energy_factor(name: &str) -> f64 {
+    if NOT_ENERGIES.iter().any(|prefix| name.starts_with(prefix)) {
+        1.0
+    } else {
+        1.0 / KJ_PER_KCAL
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn energies_are_read_in_kcal_per_mol() {
+        let log = "                      :-) GROMACS - gmx mdrun, 2023.1 (-:\n\
+                   \n           Step           Time\n          500        1.00000\n\n   Energies (kJ/mol)\n\
+                   \x20         Angle    Proper Dih.        LJ (SR)   Coulomb (SR)      Potential\n\
+                   \x20   4.18400e+02    8.36800e+01   -4.18400e+03   -4.18400e+04   -4.54824e+04\n\
+                   \x20   Temperature Pressure (bar)   Constr. rmsd         Volume        Density\n\
+                   \x20   3.00000e+02    1.00000e+00    3.00000e-06    2.70000e+01    1.01500e+03\n\n\
+                   \x20  <======  ###############  ==>\n\x20  <====  A V E R A G E S  ====>\n\
+                   \x20         Angle\n    9.99999e+02\n";
+        assert!(detect(log));
+        let mut reader = Reader::default();
+        let mut data = BTreeMap::new();
+        for line in log.lines() {
+            reader.line(line, &mut data).unwrap();
+        }
+        assert!(reader.ended);
+        let value = |name: &str| data[name][0];
+        assert_eq!((value("NSTEP"), value("TIME(PS)")), (500.0, 1.0));
+        // All the energies are converted, renamed or not.
+        assert!((value("ANGLE") - 100.0).abs() < 1e-9);
+        assert!((value("Proper Dih.") - 20.0).abs() < 1e-9);
+        assert!((value("LJ (SR)") + 1000.0).abs() < 1e-9);
+        assert!((value("Coulomb (SR)") + 10000.0).abs() < 1e-9);
+        assert!((value("EPtot") + 45482.4 / KJ_PER_KCAL).abs() < 1e-9);
+        // The other fields keep their unit.
+        assert_eq!((value("TEMP(K)"), value("PRESS"), value("Constr. rmsd")), (300.0, 1.0, 3e-6));
+        assert!((value("VOLUME") - 27000.0).abs() < 1e-9);
+        assert!((value("Density") - 1.015).abs() < 1e-9);
+        // The averages are not read.
+        assert_eq!(data["ANGLE"].len(), 1);
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use polars::prelude::*;
+use relis::{time_column, RelisError};
+
+// Read a data file written by cpptraj: a header line "#Frame name1 name2..." then whitespace separated values.
+// The first column is renamed "frame", or "TIME(PS)" if it is a time (e.g. "#Time"), to be joined on.
+// Values that are not numbers are left out (null).
+pub fn read_cpptraj(path: &Path) -> Result<DataFrame, RelisError> {
+    let text = std::fs::read_to_string(path).map_err(|e| RelisError::from(e).in_file(path))?;
+    let invalid = |message: &str| RelisError::Parse { path: Some(path.to_path_buf()), line: 1, message: message.to_string() };
+    let mut lines = text.lines().filter(|l| !l.trim().is_empty());
+    let header = lines
+        .next()
+        .and_then(|l| l.strip_prefix('#'))
+        .ok_or_else(|| invalid("no \"#Frame ...\" header line"))?;
+    let mut names: Vec<String> = header.split_whitespace().map(String::from).collect();
+    match names.first_mut() {
+        Some(first) if first.eq_ignore_ascii_case("time") => *first = "TIME(PS)".to_string(),
+        Some(first) => *first = crate::FRAME_COLUMN.to_string(),
+        None => return Err(invalid("the header line is empty")),
+    }
+    let mut columns: Vec<Vec<Option<f64>>> = vec![Vec::new(); names.len()];
+    for line in lines.filter(|l| !l.starts_with('#')) {
+        let values: Vec<&str> = line.split_whitespace().collect();
+        for (i, column) in columns.iter_mut().enumerate() {
+            column.push(values.get(i).and_then(|v| v.parse().ok()));
+        }
+    }
+    let series = names.iter().zip(columns).map(|(name, values)| Series::new(name, values)).collect();
+    Ok(DataFrame::new(series)?)
+}
+
+// Add the columns of other to the table, matching the rows on the column "on" of both tables (left join):
+// the rows of the table are kept in their order, with no value where other has no matching row.
+// When "on" is the frame column, the rows of the table are numbered from 1, as the frames in cpptraj.
+pub fn join(df: &DataFrame, other: &DataFrame, on: &str, name: &str) -> Result<DataFrame, RelisError> {
+    let keys: Vec<Option<f64>> = if on == crate::FRAME_COLUMN {
+        (1..=df.height()).map(|i| Some(i as f64)).collect()
+    } else {
+        let column = df
+            .column(on)
+            .map_err(|_| RelisError::schema(format!("cannot join {}: the table has no column \"{}\"", name, on)))?;
+        column.cast(&DataType::Float64)?.f64()?.into_iter().collect()
+    };
+    let other_keys = other
+        .column(on)
+        .map_err(|_| RelisError::schema(format!("cannot join {}: it has no column \"{}\"", name, on)))?;
+    // Keys are compared after rounding, the values being printed with different numbers of digits.
+    let rows: HashMap<i64, usize> = other_keys
+        .cast(&DataType::Float64)?
+        .f64()?
+        .into_iter()
+        .enumerate()
+        .filter_map(|(row, key)| Some((key_of(key?), row)))
+        .collect();
+    let mut df = df.clone();
+    for column in other.get_columns().iter().filter(|c| c.name() != on) {
+        if df.get_column_names().contains(&column.name()) {
+            return Err(RelisError::schema(format!("cannot join {}: the table already has a column \"{}\"", name, column.name())));
+        }
+        let values = column.cast(&DataType::Float64)?;
+        let values = values.f64()?;
+        let joined: Vec<Option<f64>> = keys
+            .iter()
+            .map(|key| rows.get(&key_of((*key)?)).and_then(|row| values.get(*row)))
+            .collect();
+        df.with_column(Series::new(column.name(), joined))?;
+    }
+    Ok(df)
+}
+
+// Join the cpptraj data files on the frame number, or on the time if that is their first column.
+pub fn join_cpptraj(mut df: DataFrame, files: &[PathBuf]) -> Result<DataFrame, RelisError> {
+    // Nothing has been read yet (e.g. when watching).
+    if df.is_empty() {
+        return Ok(df);
+    }
+    for file in files {
+        let mut dat = read_cpptraj(file)?;
+        let mut on = dat.get_columns()[0].name().to_string();
+        if on != crate::FRAME_COLUMN {
+            // The time column of the table may be named TIME.
+            let time = time_column(&df.get_column_names())
+                .ok_or_else(|| RelisError::schema(format!("cannot join {} on the time: the table has no time column", file.display())))?;
+            dat.rename(&on, &time)?;
+            on = time;
+        }
+        df = join(&df, &dat, &on, &file.display().to_string())?;
+    }
+    Ok(df)
+}
+
+// Read a table of values per frame: a CSV file with a header line, a PLUMED COLVAR file ("#! FIELDS time d1 ...")
+// or a whitespace separated file with a "#name1 name2 ..." header (e.g. written by cpptraj).
+// Values that are not numbers are left out (null).
+pub fn read_table(path: &Path) -> Result<DataFrame, RelisError> {
+    let text = std::fs::read_to_string(path).map_err(|e| RelisError::from(e).in_file(path))?;
+    let mut lines = text.lines().filter(|l| !l.trim().is_empty());
+    let header = lines
+        .next()
+        .ok_or_else(|| RelisError::Parse { path: Some(path.to_path_buf()), line: 1, message: "the file is empty".to_string() })?;
+    let csv = !header.starts_with('#') && header.contains(',');
+    let split = |line: &str| -> Vec<String> {
+        let values: Vec<&str> = if csv { line.split(',').collect() } else { line.split_whitespace().collect() };
+        values.iter().map(|v| v.trim().trim_matches('"').to_string()).collect()
+    };
+    let names = match header.strip_prefix("#! FIELDS") {
+        Some(fields) => split(fields),
+        None => split(header.trim_start_matches('#')),
+    };
+    let mut columns: Vec<Vec<Option<f64>>> = vec![Vec::new(); names.len()];
+    for line in lines.filter(|l| !l.starts_with('#')) {
+        let values = split(line);
+        for (i, column) in columns.iter_mut().enumerate() {
+            column.push(values.get(i).and_then(|v| v.parse().ok()));
+        }
+    }
+    let series = names.iter().zip(columns).map(|(name, values)| Series::new(name, values)).collect();
+    Ok(DataFrame::new(series)?)
+}
+
+// Join the tables given with --join on the column "on", the time column of the table by default.
+// A table without this column is joined on its first column if that is a time (e.g. "time" in PLUMED files).
+pub fn join_tables(mut df: DataFrame, files: &[PathBuf], on: Option<&str>) -> Result<DataFrame, RelisError> {
+    // Nothing has been read yet (e.g. when watching).
+    if df.is_empty() || files.is_empty() {
+        return Ok(df);
+    }
+    let on = match on {
+        Some(on) => on.to_string(),
+        None => time_column(&df.get_column_names())
+            .ok_or_else(|| RelisError::schema("cannot join on the time: the table has no time column, use --on"))?,
+    };
+    for file in files {
+        let mut other = read_table(file)?;
+        let first = other.get_column_names().first().map(|c| c.to_string()).unwrap_or_default();
+        if other.column(&on).is_err() && first.eq_ignore_ascii_case("time") {
+            other.rename(&first, &on)?;
+        }
+        df = join(&df, &other, &on, &file.display().to_string())?;
+    }
+    Ok(df)
+}
+
+fn key_of(value: f64) -> i64 {
+    (value * 1e6).round() as i64
+}
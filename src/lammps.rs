@@ -0,0 +1,141 @@
+// Parsing of LAMMPS logs: each run prints a header line of thermo keywords starting with "Step",
+// then one line of values per thermo output, up to the "Loop time" line. A log may hold several runs.
+// With "units real" or "units metal", the fields having an AMBER equivalent are renamed and converted to AMBER units.
+use std::collections::BTreeMap;
+use crate::parser::Parser;
+use crate::RelisError;
+
+const KCAL_PER_EV: f64 = 23.060548;
+
+// AMBER name of the LAMMPS thermo keywords that have one.
+const AMBER_NAMES: [(&str, &str); 13] = [
+    ("Step", "NSTEP"),
+    ("Time", "TIME(PS)"),
+    ("Temp", "TEMP(K)"),
+    ("PotEng", "EPtot"),
+    ("KinEng", "EKtot"),
+    ("TotEng", "Etot"),
+    ("Press", "PRESS"),
+    ("Volume", "VOLUME"),
+    ("Density", "Density"),
+    ("E_bond", "BOND"),
+    ("E_angle", "ANGLE"),
+    ("E_dihed", "DIHED"),
+    ("E_vdwl", "VDWAALS"),
+];
+
+#[derive(Default)]
+pub struct Reader {
+    // Unit style of the run, from the echo of the input.
+    units: Option<String>,
+    // Names of the columns of the current run, empty outside of the runs.
+    header: Vec<String>,
+    // Number of rows read, to fill with NaN the columns missing from some of the runs.
+    rows: usize,
+    // Last step read: the first row of a run repeats the last row of the previous one.
+    last_step: Option<f64>,
+}
+
+impl Reader {
+    // Read a line of the log, the values of the thermo outputs are added to data.
+    pub fn line(&mut self, line: &str, data: &mut BTreeMap<String, Vec<f64>>) -> Result<(), RelisError> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if let ["units", units] = fields[..] {
+            self.units = Some(units.to_string());
+            return Ok(());
+        }
+        if fields.first() == Some(&"Step") && fields.iter().all(|f| f.parse::<f64>().is_err()) {
+            self.header = fields.iter().map(|f| f.to_string()).collect();
+            return Ok(());
+        }
+        if line.starts_with("Loop time") {
+            self.header.clear();
+            return Ok(());
+        }
+        if self.header.is_empty() || fields.len() != self.header.len() {
+            return Ok(());
+        }
+        // Lines of the runs that are not values (e.g. warnings) are left out.
+        let Ok(values) = fields.iter().map(|f| f.parse::<f64>()).collect::<Result<Vec<f64>, _>>() else {
+            return Ok(());
+        };
+        let step = self.header.iter().position(|h| h == "Step").map(|i| values[i]);
+        if step.is_some() && step == self.last_step {
+            // Only the columns new in this run are taken from the repeated row.
+            for (name, value) in self.header.iter().zip(values) {
+                let (name, value) = self.convert(name, value);
+                let values = data.entry(name).or_insert_with(|| vec![f64::NAN; self.rows]);
+                if let Some(last) = values.last_mut().filter(|last| last.is_nan()) {
+                    *last = value;
+                }
+            }
+            return Ok(());
+        }
+        self.last_step = step;
+        for (name, value) in self.header.iter().zip(values) {
+            let (name, value) = self.convert(name, value);
+            data.entry(name).or_insert_with(|| vec![f64::NAN; self.rows]).push(value);
+        }
+        self.rows += 1;
+        for values in data.values_mut() {
+            values.resize(self.rows, f64::NAN);
+        }
+        Ok(())
+    }
+
+    // AMBER name and value in AMBER units, when the unit style of the run is known.
+    fn convert(&self, name: &str, value: f64) -> (String, f64) {
+        let Some(amber) = AMBER_NAMES.iter().find(|(lammps, _)| *lammps == name).map(|(_, amber)| *amber) else {
+            return (name.to_string(), value);
+        };
+        let is_energy = matches!(amber, "EPtot" | "EKtot" | "Etot" | "BOND" | "ANGLE" | "DIHED" | "VDWAALS");
+        match self.units.as_deref() {
+            // Time in fs, energies in kcal/mol, pressure in atm, volume in A^3, density in g/cm^3.
+            Some("real") => match amber {
+                "TIME(PS)" => (amber.to_string(), value / 1000.0),
+                "PRESS" => (amber.to_string(), value * 1.01325),
+                _ => (amber.to_string(), value),
+            },
+            // Time in ps, energies in eV, pressure in bar, volume in A^3, density in g/cm^3.
+            Some("metal") if is_energy => (amber.to_string(), value * KCAL_PER_EV),
+            Some("metal") => (amber.to_string(), value),
+            _ if amber == "NSTEP" => (amber.to_string(), value),
+            _ => (name.to_string(), value),
+        }
+    }
+}
+
+impl Parser for Reader {
+    fn read_line(&mut self, line: &str, data: &mut BTreeMap<String, Vec<f64>>) -> Result<bool, RelisError> {
+        self.line(line, data)?;
+        Ok(true)
+    }
+}
+
+// LAMMPS logs start with the version of the program.
+pub fn detect(head: &str) -> bool {
+    head.trim_start().starts_with("LAMMPS (")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thermo_lines_are_read_in_amber_units() {
+        let log = "LAMMPS (2 Aug 2023)\nunits metal\n\
+                   \x20  Step          Temp          PotEng         c_msd    \n\
+                   \x20        0   300           -1              0.5\n\
+                   \x20      100   310           -2              0.6\n\
+                   Loop time of 1.5 on 4 procs for 100 steps with 1000 atoms\n";
+        assert!(detect(log));
+        let mut reader = Reader::default();
+        let mut data = BTreeMap::new();
+        for line in log.lines() {
+            reader.line(line, &mut data).unwrap();
+        }
+        assert_eq!(data.keys().collect::<Vec<_>>(), vec!["EPtot", "NSTEP", "TEMP(K)", "c_msd"]);
+        assert_eq!(data["EPtot"], vec![-KCAL_PER_EV, -2.0 * KCAL_PER_EV]);
+        assert_eq!(data["c_msd"], vec![0.5, 0.6]);
+    }
+}
@@ -0,0 +1,588 @@
+// The parsing of AMBER MD .lis files, shared by the relis command line tool and its bindings.
+// The parsing works on any reader, so that it can also be used on bytes without a file system (WebAssembly).
+use std::io::BufRead;
+use std::path::Path;
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+#[cfg(feature = "dataframe")]
+use polars::prelude::*;
+use regex::Regex;
+
+pub mod amber_min;
+pub mod error;
+pub mod gamd;
+pub mod gromacs;
+pub mod lammps;
+pub mod namd;
+pub mod openmm;
+pub mod parser;
+pub mod qmmm;
+pub mod restraints;
+
+pub use error::RelisError;
+
+// Default patterns marking the beginning and the end of the section containing the values of each frame.
+pub const DEFAULT_START_PATTERN: &str = "RESULTS";
+pub const DEFAULT_END_PATTERN: &str = "A V E R A G E";
+
+// Regex capturing the categories and the values printed by sander and pmemd, with their exact names.
+// The names may have digits, underscores and slashes after their first letter (e.g. the components of the pressure
+// tensor, PRES_XX or VIR_X when they are printed, and DV/DL), a unit between parentheses (TEMP(K)) and the "1-4 "
+// of the 1-4 terms (1-4 NB, 1-4 EEL), which are read apart from NB and EEL. The values may be written with an exponent
+// (e.g. 0.1234E+05), and stuck to the "=" when they fill their field (EELEC  =-1234567.1234).
+pub const DEFAULT_REGEX: &str =
+    r"(?P<key>(?:1-4 +)?[A-Za-z][A-Za-z0-9_/]*(?:\([A-Za-z]+\))?)\s*=\s*(?P<value>-?\d+\.?\d*(?:[Ee][-+]?\d+)?)";
+
+// The programs whose output files can be read.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Engine {
+    // sander and pmemd .lis/mdout files.
+    #[default]
+    Amber,
+    // sander and pmemd minimization outputs.
+    AmberMin,
+    // NAMD logs.
+    Namd,
+    // GROMACS md.log files.
+    Gromacs,
+    // LAMMPS logs.
+    Lammps,
+    // CSV files of the StateDataReporter of OpenMM.
+    Openmm,
+    // gamd.log files of GaMD runs.
+    Gamd,
+}
+
+// Number of bytes read from the beginning of a file to detect its format.
+pub const DETECTION_BYTES: usize = 16 * 1024;
+
+impl Engine {
+    // Detect the program that wrote a file from its first bytes (DETECTION_BYTES), None if it is not recognized.
+    pub fn detect(head: &str) -> Option<Engine> {
+        parser::FORMATS.iter().find(|f| (f.detect)(head)).map(|f| f.engine)
+    }
+
+    pub const ALL: [Engine; 7] = [Engine::Amber, Engine::AmberMin, Engine::Namd, Engine::Gromacs, Engine::Lammps, Engine::Openmm,
+        Engine::Gamd];
+
+    // The engine with the given name, as returned by name().
+    pub fn from_name(name: &str) -> Option<Engine> {
+        Engine::ALL.into_iter().find(|e| e.name() == name)
+    }
+
+    // The name of the engine as given on the command line.
+    pub fn name(&self) -> &'static str {
+        parser::format(*self).name
+    }
+}
+
+// Extraction of the values of a file, one line at a time so that files can be read as they grow.
+pub struct Extractor {
+    engine: Engine,
+    parser: Box<dyn parser::Parser>,
+    // Number of lines read, for the messages.
+    lines: usize,
+}
+
+impl Extractor {
+    // The patterns and the regex are only used for AMBER files, the other formats name their values themselves.
+    pub fn new(engine: Engine, pattern_start: &Regex, pattern_end: &Regex, re: &Regex) -> Extractor {
+        let options = parser::Options { pattern_start, pattern_end, re };
+        Extractor { engine, parser: (parser::format(engine).parser)(&options), lines: 0 }
+    }
+
+    pub fn engine(&self) -> Engine {
+        self.engine
+    }
+
+    // Read a line of the file, its values are added to data.
+    // Returns true if the line is in the part of the file that is read (for AMBER files, between the two patterns).
+    // The errors give the number of the line.
+    pub fn line(&mut self, line: &str, data: &mut BTreeMap<String, Vec<f64>>) -> Result<bool, RelisError> {
+        self.lines += 1;
+        let number = self.lines;
+        self.parser.read_line(line, data).map_err(|e| e.at_line(number))
+    }
+
+    // Complete the last frame once the file has been read, the fields it lacks get a NaN.
+    pub fn finish(&self, data: &mut BTreeMap<String, Vec<f64>>) {
+        complete_frames(data);
+    }
+
+    // Returns true when the rest of the file has nothing to be read.
+    pub fn ended(&self) -> bool {
+        self.parser.ended()
+    }
+}
+
+// Open a text file and read lines from it, keep only lines between two patterns.
+// Returns a vector of strings containing the lines in the file between the two patterns.
+// 1st arg: Path to the file to be read.
+// 2nd arg: The pattern that marks the beginning of the lines to be kept.
+// 3rd arg: The pattern that marks the end of the lines to be kept.
+pub fn read_lines_until_pattern(file_path: &Path, pattern_start: &Regex, pattern_end: &Regex) -> Result<Vec<String>, RelisError> {
+    let file = std::fs::File::open(file_path).map_err(|e| RelisError::from(e).in_file(file_path))?;
+    read_lines_from(std::io::BufReader::new(file), pattern_start, pattern_end).map_err(|e| e.in_file(file_path))
+}
+
+// Same as read_lines_until_pattern, from any reader instead of a file.
+pub fn read_lines_from(reader: impl BufRead, pattern_start: &Regex, pattern_end: &Regex) -> Result<Vec<String>, RelisError> {
+    let mut lines = Vec::new();
+    let mut section = Section::default();
+    for line in Lines::new(reader) {
+        let line = line?;
+        if section.keep(&line, pattern_start, pattern_end) {
+            lines.push(line);
+        }
+        if section.ended {
+            break;
+        }
+    }
+    Ok(lines)
+}
+
+// The lines of a reader, as with BufRead::lines, except that the lines that are not text (invalid UTF-8 or NUL bytes,
+// e.g. the binary garbage left in the output of a crashed GPU run) are skipped and counted instead of ending the
+// reading with an error, so that the frames around them are kept.
+pub struct Lines<R> {
+    reader: R,
+    buf: Vec<u8>,
+    // Number of lines skipped.
+    pub invalid: usize,
+    // Number of bytes read, the skipped lines included.
+    pub bytes: u64,
+}
+
+impl<R: BufRead> Lines<R> {
+    pub fn new(reader: R) -> Lines<R> {
+        Lines { reader, buf: Vec::new(), invalid: 0, bytes: 0 }
+    }
+}
+
+impl<R: BufRead> Iterator for Lines<R> {
+    type Item = std::io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.buf.clear();
+            match self.reader.read_until(b'\n', &mut self.buf) {
+                Ok(0) => return None,
+                Ok(n) => self.bytes += n as u64,
+                Err(e) => return Some(Err(e)),
+            }
+            match text_line(&self.buf) {
+                Some(line) => return Some(Ok(line.to_string())),
+                None => self.invalid += 1,
+            }
+        }
+    }
+}
+
+// The lines of bytes already in memory (e.g. a file mapped in memory), borrowed instead of copied in a String each.
+// The lines that are not text are skipped and counted, as with Lines.
+pub struct ByteLines<'a> {
+    rest: &'a [u8],
+    // Number of lines skipped.
+    pub invalid: usize,
+    // Number of bytes read, the skipped lines included.
+    pub bytes: u64,
+}
+
+impl<'a> ByteLines<'a> {
+    pub fn new(bytes: &'a [u8]) -> ByteLines<'a> {
+        ByteLines { rest: bytes, invalid: 0, bytes: 0 }
+    }
+}
+
+impl<'a> Iterator for ByteLines<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        while !self.rest.is_empty() {
+            let end = self.rest.iter().position(|b| *b == b'\n').map_or(self.rest.len(), |i| i + 1);
+            let (line, rest) = self.rest.split_at(end);
+            self.rest = rest;
+            self.bytes += end as u64;
+            match text_line(line) {
+                Some(line) => return Some(line),
+                None => self.invalid += 1,
+            }
+        }
+        None
+    }
+}
+
+// A line without its end of line, None if it is not text (invalid UTF-8 or NUL bytes).
+fn text_line(line: &[u8]) -> Option<&str> {
+    let end = line.len() - line.ends_with(b"\n") as usize;
+    let end = end - line[..end].ends_with(b"\r") as usize;
+    std::str::from_utf8(&line[..end]).ok().filter(|line| !line.contains('\0'))
+}
+
+// Progress of the reading of a file, to keep only the lines between two patterns.
+#[derive(Default)]
+pub struct Section {
+    pub started: bool,
+    pub ended: bool,
+}
+
+impl Section {
+    // Update the progress with a new line, return true if the line is to be kept.
+    pub fn keep(&mut self, line: &str, pattern_start: &Regex, pattern_end: &Regex) -> bool {
+        if self.ended {
+            return false;
+        }
+        if pattern_start.is_match(line) {
+            self.started = true;
+        }
+        if pattern_end.is_match(line) {
+            self.ended = true;
+        }
+        self.started && !self.ended
+    }
+}
+
+// Alignment of the values of the frames of AMBER files: a field missing from a frame (e.g. printed as "********"
+// or not matched by the regex) gets a NaN in this frame, so that the values of all the fields stay in step.
+#[derive(Default)]
+pub struct Frames {
+    // The field marking the beginning of a frame: NSTEP, or without it the first field of the first line read.
+    marker: Option<String>,
+    // Number of frames started.
+    count: usize,
+}
+
+impl Frames {
+    // Add the values read from a line to data.
+    pub fn add(&mut self, line: &str, line_data: BTreeMap<String, Vec<f64>>, data: &mut BTreeMap<String, Vec<f64>>) {
+        if line_data.is_empty() {
+            return;
+        }
+        let marker = self.marker.get_or_insert_with(|| frame_marker(line, &line_data));
+        if line_data.contains_key(marker.as_str()) {
+            // The previous frames are complete.
+            for values in data.values_mut() {
+                values.resize(values.len().max(self.count), f64::NAN);
+            }
+            self.count += 1;
+        }
+        for (key, new) in line_data {
+            // A field seen for the first time has no value in the previous frames.
+            let values = data.entry(key).or_default();
+            values.resize(values.len().max(self.count.saturating_sub(1)), f64::NAN);
+            values.extend(new);
+        }
+    }
+}
+
+// The field marking the frames: NSTEP if the line has it, else the field found first in the line (those whose name
+// is not in the line, e.g. numbered, after them).
+pub fn frame_marker(line: &str, line_data: &BTreeMap<String, Vec<f64>>) -> String {
+    if line_data.contains_key("NSTEP") {
+        return "NSTEP".to_string();
+    }
+    let first = line_data.keys().min_by_key(|k| line.find(k.as_str()).unwrap_or(usize::MAX));
+    first.cloned().unwrap_or_default()
+}
+
+// Give all the fields as many values as the longest one, the missing values being NaN.
+pub fn complete_frames(data: &mut BTreeMap<String, Vec<f64>>) {
+    let frames = data.values().map(Vec::len).max().unwrap_or(0);
+    for values in data.values_mut() {
+        values.resize(frames, f64::NAN);
+    }
+}
+
+// A function that extract the list of the differents values available for each frame in the .lis file using regex.
+// The function returns a Result with a BTreeMap of <String, float> that contains the names of the name and values for each frame, or an error.
+// 1st arg: A vector of strings containing the lines that contain the values.
+// 2nd arg: The regex capturing the categories in its "key" group and the values in its "value" group.
+pub fn extract_values(lines: &[String], re: &Regex) -> Result<BTreeMap<String, Vec<f64>>, RelisError> {
+    // Create a BTreeMap that will contain the types of values and their values for each frame.
+    let mut data = BTreeMap::new();
+    let mut frames = Frames::default();
+    let default = is_default_regex(re);
+    // For each line, search and add the value to the corresponding key in the data map.
+    for line in lines {
+        let mut line_data = BTreeMap::new();
+        read_fields(line, re, default, &mut line_data)?;
+        frames.add(line, line_data, &mut data);
+    }
+    complete_frames(&mut data);
+    Ok(data)
+}
+
+// Add the values of a line of AMBER results to data, with the name captured in the "key" group of the regex.
+// With the default regex, the lines of the QM/MM energies are read apart and their fields prefixed (see qmmm).
+// default tells whether re is the default regex (see is_default_regex), decided once per file rather than per line.
+pub fn read_fields(line: &str, re: &Regex, default: bool, data: &mut BTreeMap<String, Vec<f64>>) -> Result<(), RelisError> {
+    if skip_line(line, default) || (default && (qmmm::line(line, data)? || restraints::line(line, data)?)) {
+        return Ok(());
+    }
+    let mut names = Vec::new();
+    // Most lines are read without the regex, which only reads the lines that the fast path does not understand.
+    if default {
+        if let Some(fields) = split_fields(line) {
+            for (key, value) in fields {
+                add_field(qmmm::field_name(key), value, &mut names, data);
+            }
+            return Ok(());
+        }
+    }
+    for cap in re.captures_iter(line) {
+        // The spaces of the 1-4 terms are made single, so that "1-4  NB" and "1-4 NB" are one column.
+        let key = cap["key"].split_whitespace().collect::<Vec<_>>().join(" ");
+        let key = if default { qmmm::field_name(&key) } else { key };
+        // Convert the value to a float
+        let v = parse_float(&cap["value"])?;
+        add_field(key, v, &mut names, data);
+    }
+    Ok(())
+}
+
+// Add a value read from a line to data. A name already read from the line is numbered (EELEC_2), so that each field
+// of a frame has its own column and the frames stay in step.
+fn add_field(key: String, value: f64, names: &mut Vec<String>, data: &mut BTreeMap<String, Vec<f64>>) {
+    let count = names.iter().filter(|n| **n == key).count();
+    let name = if count == 0 { key.clone() } else { format!("{}_{}", key, count + 1) };
+    names.push(key);
+    data.entry(name).or_default().push(value);
+}
+
+// Read the "KEY = value" pairs of a line by splitting it on the "=", giving the same fields as the default regex.
+// Returns None when the line has anything unusual (names or values that are not plain, missing spaces around
+// the "="), the line is then read with the regex.
+fn split_fields(line: &str) -> Option<Vec<(&str, f64)>> {
+    let mut parts = line.split('=');
+    let mut key = parts.next()?;
+    let mut fields = Vec::new();
+    for part in parts {
+        if !key.ends_with(char::is_whitespace) || !part.starts_with(char::is_whitespace) {
+            return None;
+        }
+        // The key is the end of the previous part, after its value.
+        let key_name = key.trim();
+        if !is_plain_key(key_name) {
+            return None;
+        }
+        let part = part.trim_start();
+        let end = part.find(char::is_whitespace).unwrap_or(part.len());
+        let (value, rest) = part.split_at(end);
+        if !is_plain_number(value) {
+            return None;
+        }
+        fields.push((key_name, parse_float(value).ok()?));
+        key = rest;
+    }
+    Some(fields)
+}
+
+// A name as read by the default regex: letters, digits, underscores and slashes starting with a letter, possibly
+// followed by a unit between parentheses (TEMP(K)), or a 1-4 term (1-4 NB).
+fn is_plain_key(key: &str) -> bool {
+    let key = key.strip_prefix("1-4 ").unwrap_or(key);
+    let end = key.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '/')).unwrap_or(key.len());
+    let (name, suffix) = key.split_at(end);
+    let unit = suffix.strip_prefix('(').and_then(|s| s.strip_suffix(')'));
+    name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && (suffix.is_empty() || unit.is_some_and(|u| !u.is_empty() && u.chars().all(|c| c.is_ascii_alphabetic())))
+}
+
+// A number as read by the default regex: -?digits[.digits][E[+-]digits].
+fn is_plain_number(s: &str) -> bool {
+    let s = s.strip_prefix('-').unwrap_or(s);
+    let (mantissa, exponent) = match s.find(['E', 'e']) {
+        Some(i) => (&s[..i], Some(&s[i + 1..])),
+        None => (s, None),
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    let exponent_ok = exponent.is_none_or(|e| {
+        let e = e.strip_prefix(['+', '-']).unwrap_or(e);
+        !e.is_empty() && digits(e)
+    });
+    !integer.is_empty() && digits(integer) && digits(fraction) && exponent_ok
+}
+
+// Parse a number with the lexical crate, several times faster than str::parse on the values of large files.
+pub fn parse_float(s: &str) -> Result<f64, RelisError> {
+    lexical_core::parse::<f64>(s.as_bytes()).map_err(|e| RelisError::parse(format!("invalid value \"{}\": {}", s, e)))
+}
+
+// The "name = value" pairs of a line whose names may have spaces (e.g. "SCF Energy = -23.1 KCal/mol"),
+// the spaces and dashes of the names being replaced by underscores.
+pub(crate) fn named_values(text: &str) -> Result<Vec<(String, f64)>, RelisError> {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let re = RE.get_or_init(|| {
+        Regex::new(r"(?P<key>[A-Za-z][A-Za-z0-9\- ]*?)\s*=\s*(?P<value>-?\d+\.?\d*(?:[Ee][-+]?\d+)?)").unwrap()
+    });
+    re.captures_iter(text)
+        .map(|cap| Ok((cap["key"].trim().replace([' ', '-'], "_"), cap["value"].parse::<f64>()?)))
+        .collect()
+}
+
+// Return true if re is the default regex, whose lines are read with the fast path and the special lines (QM/MM,
+// restraints, skipped lines) recognized.
+pub fn is_default_regex(re: &Regex) -> bool {
+    re.as_str() == DEFAULT_REGEX
+}
+
+// Return true if the values of the line must not be extracted.
+// The lines skipped are only a problem for the default regex, a regex given by the user decides alone: the kinetic
+// energies of the center of mass ("KE Trans = ..."), the Ewald error estimate and the information lines starting with
+// "|" (e.g. "| Local SIZE OF NONBOND LIST = 12345" at the top of the RESULTS section). Only whole words are looked
+// for, so that the SGLD terms (SGLF, SGHF, TEMPSG...) printed among them are kept.
+pub fn skip_line(line: &str, default: bool) -> bool {
+    default
+        && (line.trim_start().starts_with('|')
+            || line.split(|c: char| !c.is_alphanumeric()).any(|w| w == "KE" || w.starts_with("err")))
+}
+
+// Create a DataFrame from the values extracted from one file, with a column for each key/values pair.
+// The missing values (NaN) are null in the DataFrame, so that they are left out of the statistics.
+#[cfg(feature = "dataframe")]
+pub fn to_dataframe(data: &BTreeMap<String, Vec<f64>>) -> Result<DataFrame, RelisError> {
+    let mut df = DataFrame::new::<Series>(vec![])?;
+    // Iterate over the BTreeMap and create a new column for each key/values pair.
+    for (key, values) in data.iter() {
+        let values: Vec<Option<f64>> = values.iter().map(|v| Some(*v).filter(|v| !v.is_nan())).collect();
+        let s = Series::new(key, values);
+        df.with_column(s)?;
+    }
+    Ok(df)
+}
+
+// Return the name of the time column: "TIME(PS)", or "TIME" as printed by some versions of AMBER.
+pub fn time_column(columns: &[&str]) -> Option<String> {
+    columns
+        .iter()
+        .find(|c| c.eq_ignore_ascii_case("TIME(PS)") || c.eq_ignore_ascii_case("TIME"))
+        .map(|c| c.to_string())
+}
+
+// Return true if the line reports a problem in the simulation.
+pub fn is_warning(line: &str) -> bool {
+    line.contains("WARNING") || line.starts_with("Warning:") || line.contains("NaN") || line.contains("*****")
+}
+
+// Read the values of the frames of a .lis file into a DataFrame, with a column for each field.
+// 1st arg: Path to the file to be read.
+// 2nd arg: The pattern that marks the beginning of the lines to be read.
+// 3rd arg: The pattern that marks the end of the lines to be read.
+// 4th arg: The regex capturing the categories in its "key" group and the values in its "value" group.
+#[cfg(feature = "dataframe")]
+pub fn parse_file(file_path: &Path, pattern_start: &Regex, pattern_end: &Regex, re: &Regex) -> Result<DataFrame, RelisError> {
+    let lines = read_lines_until_pattern(file_path, pattern_start, pattern_end)?;
+    to_dataframe(&extract_values(&lines, re).map_err(|e| e.in_file(file_path))?)
+}
+
+// Extract the values of the frames of a .lis file already in memory, with a vector of values per field.
+// The arguments are the same as for parse_file, with the content of the file instead of its path.
+pub fn parse_bytes(bytes: &[u8], pattern_start: &Regex, pattern_end: &Regex, re: &Regex) -> Result<BTreeMap<String, Vec<f64>>, RelisError> {
+    let lines = read_lines_from(bytes, pattern_start, pattern_end)?;
+    extract_values(&lines, re)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The fields of a line as read by the default regex, without the fast path.
+    fn regex_fields(line: &str) -> Vec<(String, f64)> {
+        let re = Regex::new(DEFAULT_REGEX).unwrap();
+        re.captures_iter(line).map(|cap| (cap["key"].to_string(), cap["value"].parse().unwrap())).collect()
+    }
+
+    fn fast_fields(line: &str) -> Option<Vec<(String, f64)>> {
+        split_fields(line).map(|fields| fields.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+    }
+
+    #[test]
+    fn fast_path_matches_regex() {
+        let lines = [
+            " NSTEP =     1000   TIME(PS) =     102.000  TEMP(K) =   300.12  PRESS =   -12.3",
+            " Etot   =    -71234.5678  EKtot   =     17890.1234  EPtot      =    -89124.8012",
+            " BOND   =       512.3456  ANGLE   =      1345.6789  DIHED      =      1789.0123",
+            " 1-4 NB =       567.8901  1-4 EEL =      6789.0123  VDWAALS    =     10234.5678",
+            " EKCMT  =      8012.3456  VIRIAL  =      8123.4567  VOLUME     =    345678.9012",
+            " PRES_XX =  0.1234E+05  VIR_X =  -0.5678E-02  SGLF =  1.0000",
+        ];
+        for line in lines {
+            assert_eq!(fast_fields(line), Some(regex_fields(line)), "{}", line);
+        }
+    }
+
+    #[test]
+    fn fast_path_reads_exponents_and_names_with_digits() {
+        let fields = fast_fields(" PRES_XX =  0.1234E+05  VIR_X =  -0.5678E-02").unwrap();
+        assert_eq!(fields, vec![("PRES_XX".to_string(), 12340.0), ("VIR_X".to_string(), -0.005678)]);
+        let fields = fast_fields(" 1-4 NB =  1.5  1-4 EEL =  -2.5").unwrap();
+        assert_eq!(fields, vec![("1-4 NB".to_string(), 1.5), ("1-4 EEL".to_string(), -2.5)]);
+    }
+
+    #[test]
+    fn ekcmt_virial_volume_line_is_read() {
+        let re = Regex::new(DEFAULT_REGEX).unwrap();
+        let mut data = BTreeMap::new();
+        read_fields(" EKCMT  =      8012.3456  VIRIAL  =   0.1234E+05  VOLUME     =    345678.9012", &re, true, &mut data).unwrap();
+        assert_eq!(data.keys().collect::<Vec<_>>(), vec!["EKCMT", "VIRIAL", "VOLUME"]);
+        assert_eq!(data["VIRIAL"], vec![12340.0]);
+        // The kinetic energies of the centers of mass and the Ewald error are still left out.
+        assert!(skip_line(" KE Trans =     0.1234  KE Rot =     0.5678  C.O.M. Vel =    0.0001", true));
+        assert!(skip_line(" Ewald error estimate:   0.1234E-03", true));
+    }
+
+    #[test]
+    fn overflow_values_are_left_to_the_regex() {
+        let line = " EELEC  = **************  EHBOND  =        0.0000";
+        assert_eq!(fast_fields(line), None);
+        assert_eq!(regex_fields(line), vec![("EHBOND".to_string(), 0.0)]);
+        let mut data = BTreeMap::new();
+        read_fields(line, &Regex::new(DEFAULT_REGEX).unwrap(), true, &mut data).unwrap();
+        assert_eq!(data.keys().collect::<Vec<_>>(), vec!["EHBOND"]);
+    }
+
+    #[test]
+    fn repeated_fields_and_1_4_terms_get_their_own_column() {
+        let frame = [
+            " NSTEP =      500   TIME(PS) =       1.000  TEMP(K) =   300.12  PRESS =     0.0",
+            " 1-4 NB =       5.0000  1-4 EEL =      50.0000  VDWAALS    =     -10.0000",
+            " EELEC  =     -100.0000  EGB     =      -20.0000  EELEC      =      -1.0000",
+            // The second frame is read by the regex, which makes the spaces of the 1-4 terms single.
+            " NSTEP =     1000   TIME(PS) =       2.000  TEMP(K) =   301.50  PRESS =     0.0",
+            " 1-4  NB =       6.0000  1-4  EEL =      60.0000  VDWAALS    =     -11.0000",
+            " EELEC  =     -200.0000  EGB     =      -30.0000  EELEC      = -0.2E+01",
+        ];
+        assert_eq!(fast_fields(frame[4]), None);
+        let lines: Vec<String> = frame.iter().map(|l| l.to_string()).collect();
+        let data = extract_values(&lines, &Regex::new(DEFAULT_REGEX).unwrap()).unwrap();
+        assert_eq!(data.keys().collect::<Vec<_>>(),
+            vec!["1-4 EEL", "1-4 NB", "EELEC", "EELEC_2", "EGB", "NSTEP", "PRESS", "TEMP(K)", "TIME(PS)", "VDWAALS"]);
+        assert_eq!(data["EELEC"], vec![-100.0, -200.0]);
+        assert_eq!(data["EELEC_2"], vec![-1.0, -2.0]);
+        assert_eq!(data["1-4 NB"], vec![5.0, 6.0]);
+        assert_eq!(data["1-4 EEL"], vec![50.0, 60.0]);
+    }
+
+    #[test]
+    fn frames_start_at_nstep_after_the_nonbond_list_lines() {
+        let lines = [
+            "| Local SIZE OF NONBOND LIST =    1234567",
+            "| TOTAL SIZE OF NONBOND LIST =    1234567",
+            " NSTEP =      500   TIME(PS) =       1.000  TEMP(K) =   300.00  PRESS =     0.0",
+            " BOND   =       100.0000  ANGLE   =       200.0000  DIHED      =       300.0000",
+            " NSTEP =     1000   TIME(PS) =       2.000  TEMP(K) =   301.00  PRESS =     0.0",
+            " BOND   =       101.0000  ANGLE   = **************  DIHED      =       301.0000",
+            " NSTEP =     1500   TIME(PS) =       3.000  TEMP(K) =   302.00  PRESS =     0.0",
+            " BOND   =       102.0000  ANGLE   =       202.0000  DIHED      =       302.0000",
+        ];
+        let lines: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+        let data = extract_values(&lines, &Regex::new(DEFAULT_REGEX).unwrap()).unwrap();
+        assert!(!data.contains_key("LIST"));
+        assert_eq!(data["NSTEP"], vec![500.0, 1000.0, 1500.0]);
+        assert_eq!(data["ANGLE"][0], 200.0);
+        assert!(data["ANGLE"][1].is_nan());
+        assert_eq!(data["ANGLE"][2], 202.0);
+    }
+}
@@ -1,96 +1,1698 @@
-use std::io::BufRead;
-use std::path::PathBuf;
+mod alert;
+mod analysis;
+mod archive;
+mod bench;
+mod cache;
+mod chain;
+mod derive;
+mod dry_run;
+mod fields;
+mod gnuplot;
+mod join;
+mod manifest;
+mod modified;
+mod netcdf;
+mod params;
+mod read_ahead;
+mod remote;
+mod serve;
+mod tui;
+mod watch;
+
+use std::path::{Path, PathBuf};
 use glob::glob;
-use std::str::FromStr;
 use std::collections::BTreeMap;
 use polars::prelude::*;
 use std::error::Error;
+use std::io::{BufRead, Read};
+use std::process::ExitCode;
 use regex::Regex;
+use clap::{Args, CommandFactory, Parser, Subcommand};
+use indicatif::{ProgressBar, ProgressStyle};
+use std::time::Instant;
+use manifest::{FailedFile, Manifest, ParsedFile, SkippedFile};
+use relis::{is_warning, time_column, to_dataframe, ByteLines, Engine, Extractor, Lines, RelisError};
+use relis::{DEFAULT_END_PATTERN, DEFAULT_REGEX, DEFAULT_START_PATTERN};
+
+/// A CLI tool that extracts values from AMBER MD .lis files.
+#[derive(Parser)]
+#[command(version, about, args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    input: Option<InputArgs>,
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Watch the matched files and update the CSV and the averages as new frames are written.
+    Watch(Box<watch::WatchArgs>),
+    /// Show a live dashboard of running simulations: plots, progress of each run and warnings.
+    Tui(tui::TuiArgs),
+    /// Serve the extracted table and its summary over HTTP, as JSON and as a minimal HTML page.
+    Serve(Box<serve::ServeArgs>),
+    /// Time the reading, the parsing, the assembly of the table and the writing of the matched files, in s and MB/s.
+    Bench(bench::BenchArgs),
+    /// List the fields read from the AMBER files with their unit and their meaning.
+    Fields,
+    /// Print the parameters of the AMBER runs (&cntrl and &ewald namelists) that differ between the files.
+    Params(params::ParamsArgs),
+    /// Print a completion script for the given shell, e.g. `relis completions bash > ~/.local/share/bash-completion/completions/relis`.
+    Completions {
+        shell: clap_complete::Shell,
+    },
+}
+
+/// Options selecting the files to read, the lines to read in them and the order of the table.
+#[derive(Args, Clone)]
+pub struct InputArgs {
+    /// Files to read: "path/to/directory/pattern" (glob style). With several patterns, e.g. "run1/prod*.lis"
+    /// "run2/prod*.lis", the files are put together with a "run" column holding the name of their directory,
+    /// and the outputs are written in the current directory.
+    #[arg(required_unless_present = "files_from", conflicts_with = "files_from")]
+    patterns: Vec<String>,
+    /// Read the files listed in FILE instead, one path per line ("-" for stdin), or the files of a JSON manifest.
+    /// The outputs are written in the current directory.
+    #[arg(long, value_name = "FILE")]
+    files_from: Option<PathBuf>,
+    /// Take the patterns as paths of files, without interpreting *, ? and [ ] (e.g. run[1]/prod.lis).
+    /// A pattern naming an existing file is always taken as it is.
+    #[arg(long)]
+    literal: bool,
+    /// What the run column holds, the frames of a run being taken together in the statistics: dir (the directory of
+    /// the file, the default with several patterns), file, or a setting echoed in the AMBER files, e.g. temp0.
+    #[arg(long, value_name = "KEY", value_parser = parse_group_key)]
+    group_by: Option<GroupKey>,
+    /// Order in which the files are read: name (prod2 before prod10) or mtime (oldest modified first).
+    /// By default, in the order of the patterns or of the list.
+    #[arg(long, value_enum)]
+    order: Option<modified::FileOrder>,
+    /// Only read the files modified after this date (UTC), e.g. 2024-06-01 or 2024-06-01T12:00:00.
+    #[arg(long, value_name = "DATE", value_parser = modified::parse_date, conflicts_with = "newer_than_file")]
+    newer_than: Option<std::time::SystemTime>,
+    /// Only read the files modified after FILE.
+    #[arg(long, value_name = "FILE")]
+    newer_than_file: Option<PathBuf>,
+    /// Program that wrote the files, detected from the beginning of each file if not given (AMBER if not recognized).
+    #[arg(long, value_enum)]
+    engine: Option<Engine>,
+    /// Regex matching the line from which the values are read (AMBER files).
+    #[arg(long, default_value = DEFAULT_START_PATTERN, value_parser = Regex::new)]
+    start_pattern: Regex,
+    /// Regex matching the line at which the reading stops (AMBER files).
+    #[arg(long, default_value = DEFAULT_END_PATTERN, value_parser = Regex::new)]
+    end_pattern: Regex,
+    /// Regex extracting the values, with the name captured in a "key" group and the number in a "value" group (AMBER files).
+    #[arg(long, default_value = DEFAULT_REGEX, value_parser = parse_regex)]
+    regex: Regex,
+    /// Sort the frames by this column instead of the time, append ":desc" for descending order.
+    #[arg(long, value_name = "COLUMN[:desc]", value_parser = parse_sort_key, conflicts_with = "no_sort")]
+    sort_by: Option<SortKey>,
+    /// Keep the frames in the order in which they were read.
+    #[arg(long)]
+    no_sort: bool,
+    /// cpptraj data file (.dat) whose columns are added to the table, matching its frames with the rows of the table
+    /// (numbered from 1 after sorting), or with the time if its first column is the time. Can be repeated.
+    #[arg(long, value_name = "FILE")]
+    cpptraj: Vec<PathBuf>,
+    /// Table of values per frame whose columns are added to the table, matching its rows on the column given with
+    /// --on (the time by default): a CSV file, a PLUMED COLVAR file or a whitespace separated file. Can be repeated.
+    #[arg(long, value_name = "FILE")]
+    join: Vec<PathBuf>,
+    /// Column on which the tables given with --join are matched, e.g. 'TIME(PS)' or NSTEP.
+    #[arg(long, value_name = "COLUMN", requires = "join")]
+    on: Option<String>,
+    /// Only keep these fields (and the time), comma separated, e.g. 'TEMP(K),Etot'.
+    #[arg(long, value_delimiter = ',', value_parser = fields::FieldParser, hide_possible_values = true)]
+    fields: Vec<String>,
+    /// Only keep a set of fields (and the time): thermo (TEMP(K), PRESS, Density, VOLUME), energy (Etot, EPtot,
+    /// EKtot and the energy terms) or ti (DV/DL, EPtot). Can be repeated and combined with --fields.
+    #[arg(long, value_enum)]
+    preset: Vec<fields::Preset>,
+    /// Order of the columns after the run and the time: input (as the fields appear in the files: Etot, EKtot, EPtot,
+    /// BOND, ...), alpha (alphabetical) or preset (as given with --fields and --preset, else as AMBER prints them).
+    #[arg(long, value_enum, default_value_t)]
+    column_order: fields::ColumnOrder,
+    /// Add a column computed from the others, e.g. 'EDIFF = Etot - EPtot - EKtot', with + - * / and parentheses.
+    /// Columns are named as they are (TEMP(K)) or between double quotes. Can be repeated, a column can use the ones before it.
+    #[arg(long, value_name = "NAME = EXPRESSION", value_parser = derive::parse_derived)]
+    derive: Vec<derive::Derived>,
+    /// Size of the reads of the files, e.g. 8M: large reads are faster on network file systems (Lustre, NFS).
+    #[arg(long, value_name = "SIZE", default_value = "1M", value_parser = parse_size)]
+    read_buffer: usize,
+    /// Read the local files in a thread, ahead of the parsing, so that reading and parsing overlap.
+    #[arg(long)]
+    read_ahead: bool,
+    /// Read the local files with reads instead of mapping them in memory, e.g. files that may be rewritten meanwhile.
+    #[arg(long)]
+    no_mmap: bool,
+}
+
+impl InputArgs {
+    // True if the patterns are paths of files, which are read as they are instead of being matched.
+    fn literal(&self) -> bool {
+        self.literal || (!self.patterns.is_empty() && self.patterns.iter().all(|p| Path::new(p).is_file()))
+    }
+
+    // Keep the files selected with --newer-than or --newer-than-file, in the order given with --order.
+    fn select_files(&self, files: Vec<PathBuf>) -> Result<Vec<PathBuf>, RelisError> {
+        let newer_than = match &self.newer_than_file {
+            Some(reference) => Some(modified::modified(reference)?),
+            None => self.newer_than,
+        };
+        modified::select(files, self.order, newer_than)
+    }
+
+    // The engine given on the command line, or the one detected from the first bytes of the file.
+    fn engine(&self, head: &[u8]) -> Option<Engine> {
+        self.engine.or_else(|| Engine::detect(&String::from_utf8_lossy(head)))
+    }
+
+    // Open a file, local or remote, with the engine given on the command line or detected from its first bytes.
+    fn open(&self, file: &Path) -> Result<OpenedFile, RelisError> {
+        let mut reader: Box<dyn Read> = if remote::is_remote(&file.to_string_lossy()) {
+            Box::new(remote::open(file)?)
+        } else if archive::is_archive(&file.to_string_lossy()) {
+            Box::new(archive::open(file)?)
+        } else {
+            let local = std::fs::File::open(file).map_err(|e| RelisError::from(e).in_file(file))?;
+            if self.read_ahead {
+                Box::new(read_ahead::ReadAhead::new(local, self.read_buffer))
+            } else {
+                Box::new(local)
+            }
+        };
+        let mut head = Vec::new();
+        (&mut reader)
+            .take(relis::DETECTION_BYTES as u64)
+            .read_to_end(&mut head)
+            .map_err(|e| RelisError::from(e).in_file(file))?;
+        let engine = self.engine(&head);
+        let reader = std::io::Cursor::new(head).chain(reader);
+        let reader = Box::new(std::io::BufReader::with_capacity(self.read_buffer, reader));
+        Ok(OpenedFile { engine, reader })
+    }
+
+    // Map a local file in memory, so that its lines are read without copying them. None for the remote files, the
+    // members of archives, the empty files, and with --read-ahead or --no-mmap.
+    fn map(&self, file: &Path) -> Result<Option<memmap2::Mmap>, RelisError> {
+        let name = file.to_string_lossy();
+        if self.no_mmap || self.read_ahead || remote::is_remote(&name) || archive::is_archive(&name) {
+            return Ok(None);
+        }
+        let local = std::fs::File::open(file).map_err(|e| RelisError::from(e).in_file(file))?;
+        if local.metadata().map_err(|e| RelisError::from(e).in_file(file))?.len() == 0 {
+            return Ok(None);
+        }
+        // Safety: the file must not be truncated while it is mapped, which would end relis with SIGBUS. The
+        // outputs of the simulations are only appended to, use --no-mmap for files that may be rewritten.
+        let map = unsafe { memmap2::Mmap::map(&local) }.map_err(|e| RelisError::from(e).in_file(file))?;
+        Ok(Some(map))
+    }
+
+    // The options changing the values read from a file, for the cache.
+    fn options(&self) -> String {
+        format!("engine={:?} start={} end={} regex={}", self.engine, self.start_pattern, self.end_pattern, self.regex)
+    }
+
+    fn extractor(&self, engine: Engine) -> Extractor {
+        Extractor::new(engine, &self.start_pattern, &self.end_pattern, &self.regex)
+    }
+}
+
+// A file opened for reading, with the engine that wrote it if it is known.
+struct OpenedFile {
+    engine: Option<Engine>,
+    reader: Box<dyn BufRead>,
+}
+
+// What the run column holds, given with --group-by.
+#[derive(Clone)]
+enum GroupKey {
+    // The directory (or archive) of the file.
+    Dir,
+    File,
+    // A setting echoed at the beginning of the files, with a lowercase name.
+    Setting(String),
+}
+
+fn parse_group_key(s: &str) -> Result<GroupKey, String> {
+    match s.trim() {
+        "" => Err("the key is empty".to_string()),
+        "dir" | "run" => Ok(GroupKey::Dir),
+        "file" => Ok(GroupKey::File),
+        setting => Ok(GroupKey::Setting(setting.to_lowercase())),
+    }
+}
+
+// A column to sort the frames by.
+#[derive(Clone)]
+struct SortKey {
+    column: String,
+    descending: bool,
+}
+
+// Parse a "COLUMN[:desc]" (or "COLUMN:asc") sort key given on the command line.
+fn parse_sort_key(s: &str) -> Result<SortKey, String> {
+    let (column, descending) = match s.rsplit_once(':') {
+        Some((column, "desc")) => (column, true),
+        Some((column, "asc")) => (column, false),
+        _ => (s, false),
+    };
+    if column.is_empty() {
+        return Err("the column name is empty".to_string());
+    }
+    Ok(SortKey { column: column.to_string(), descending })
+}
+
+// Compile the extraction regex given on the command line, it must have the named groups "key" and "value".
+fn parse_regex(s: &str) -> Result<Regex, String> {
+    let re = Regex::new(s).map_err(|e| e.to_string())?;
+    for group in ["key", "value"] {
+        if !re.capture_names().any(|name| name == Some(group)) {
+            return Err(format!("the regex has no named group \"{}\", e.g. (?P<{}>...)", group, group));
+        }
+    }
+    Ok(re)
+}
+
+/// Options telling what to write from the extracted values.
+#[derive(Args)]
+pub struct OutputArgs {
+    /// Also write a gnuplot script (plot.gp) next to the CSV, plotting each column against TIME(PS).
+    #[arg(long)]
+    gnuplot: bool,
+    /// Also write the table as a NetCDF file (LISFILES_SUMMARY.nc) next to the CSV, one variable per column with its units.
+    #[arg(long)]
+    netcdf: bool,
+    /// Write a JSON manifest describing the files read, the columns found and the files written.
+    #[arg(long, value_name = "FILE")]
+    manifest: Option<PathBuf>,
+    /// Rename columns before writing, e.g. 'TEMP(K)=temperature,Etot=total_energy'.
+    #[arg(long, value_delimiter = ',', value_parser = parse_rename)]
+    rename: Vec<(String, String)>,
+    /// TOML file mapping column names to new names, e.g. "TEMP(K)" = "temperature". --rename takes precedence.
+    #[arg(long, value_name = "FILE")]
+    rename_file: Option<PathBuf>,
+    /// Write a tidy table with three columns (time, variable, value) instead of one column per field.
+    #[arg(long)]
+    long: bool,
+    /// Number of decimals of the values written in the CSV file.
+    #[arg(long, value_name = "DIGITS")]
+    precision: Option<usize>,
+    /// Separator of the columns of the CSV file, a single character, e.g. ';' or '\t' for a TSV file.
+    #[arg(long, default_value = ",", value_parser = parse_delimiter)]
+    delimiter: u8,
+    /// Write the CSV file without the header line of the column names.
+    #[arg(long, conflicts_with = "gnuplot")]
+    no_header: bool,
+    /// Keep the values read from each file in a .relis-cache directory next to the CSV, so that the next runs only
+    /// parse the new or modified files (local files only).
+    #[arg(long)]
+    cache: bool,
+    /// Continue a run that was interrupted or stopped by --time-limit: the files already read are taken from the
+    /// cache (as with --cache), only the others are parsed.
+    #[arg(long)]
+    resume: bool,
+    /// Stop reading new files after this many seconds, e.g. before the end of the walltime of a job, and write the
+    /// outputs of the files read. Run again with --resume to read the other files.
+    #[arg(long, value_name = "SECONDS")]
+    time_limit: Option<f64>,
+    /// Skip the files that cannot be read (unreadable or corrupt)
+    /// instead of stopping at the first one, the files skipped are listed at the end and in the manifest.
+    #[arg(long)]
+    keep_going: bool,
+    /// Exit with code 5 if warnings were found in the files (e.g. "WARNING", NaN or "*****" values).
+    #[arg(long)]
+    strict: bool,
+    /// Check that the segments of each directory, in natural order, follow each other: the first TIME(PS) (or NSTEP)
+    /// of each file must come one output interval after the last one of the previous file.
+    #[arg(long)]
+    chain_check: bool,
+    /// Report for each column the fraction of the frames having a value, the first and last time with one and the
+    /// files without it (also in the manifest), for projects whose protocol changes (e.g. NVT then NPT runs).
+    #[arg(long)]
+    coverage: bool,
+    /// Report the drift of the total energy (Etot) of NVE runs over time, in kcal/mol/ns and per degree of freedom.
+    #[arg(long)]
+    nve_report: bool,
+    /// Drift per degree of freedom (kcal/mol/ns) above which --nve-report warns.
+    #[arg(long, value_name = "KCAL_MOL_NS", default_value_t = 0.01)]
+    drift_threshold: f64,
+    /// Compare the mean TEMP(K) and PRESS of each file with the temp0 and pres0 of its &cntrl namelist (AMBER files),
+    /// when the thermostat (ntt > 0) or the barostat (ntp > 0) is on.
+    #[arg(long)]
+    target_check: bool,
+    /// Deviation of the mean temperature from temp0 (K) above which --target-check warns.
+    #[arg(long, value_name = "K", default_value_t = 3.0)]
+    temp_tolerance: f64,
+    /// Deviation of the mean pressure from pres0 (bar) above which --target-check warns.
+    #[arg(long, value_name = "BAR", default_value_t = 50.0)]
+    press_tolerance: f64,
+    /// Report the number of frames of each file and the interval between them (ntpr * dt, or the spacing of the
+    /// times), and flag the files written at a different interval than the others.
+    #[arg(long)]
+    sampling_audit: bool,
+    /// Report when the running means of Density and VOLUME stabilize, and their equilibrated values with error bars.
+    #[arg(long)]
+    npt_report: bool,
+    /// Relative deviation from the final value within which --npt-report considers the running mean stable.
+    #[arg(long, value_name = "FRACTION", default_value_t = 0.005)]
+    npt_tolerance: f64,
+    /// Only list the matched files with the fields of their first frame and their number of frames, write nothing.
+    #[arg(long)]
+    dry_run: bool,
+    /// Overwrite the outputs of a previous run (LISFILES_SUMMARY.CSV...), which are kept otherwise.
+    #[arg(long)]
+    force: bool,
+    /// Add the new frames to the existing LISFILES_SUMMARY.CSV: only the files modified since it was written are read
+    /// (unless --newer-than is given), and only their frames after the last time of each run in it are added.
+    #[arg(long, conflicts_with_all = ["timestamped", "long", "no_header"])]
+    append: bool,
+    /// Add the date and time of the run to the names of the outputs (e.g. LISFILES_SUMMARY_20261015T093000Z.CSV),
+    /// so that the outputs of the previous runs are kept.
+    #[arg(long)]
+    timestamped: bool,
+    /// Leave the beginning of each run out of the statistics, e.g. 5ns or 500 (ps), the whole table is written.
+    #[arg(long, value_name = "TIME", value_parser = parse_duration, conflicts_with = "skip_frac")]
+    skip: Option<f64>,
+    /// Leave this fraction of the frames of each run out of the statistics, e.g. 0.2, the whole table is written.
+    #[arg(long, value_name = "FRACTION", value_parser = parse_fraction)]
+    skip_frac: Option<f64>,
+    /// Print a 95% confidence interval of the mean of each column, from this many moving-block bootstrap resamples.
+    #[arg(long, value_name = "N")]
+    bootstrap: Option<usize>,
+    /// The runs (several patterns, or --group-by) are independent replicates: also print the mean over the replicates
+    /// of the mean of each one, with its standard error, instead of only the statistics of all the frames together.
+    #[arg(long)]
+    replicates: bool,
+    /// Also print the means of accelerated MD runs (aMD, GaMD) reweighted by the boost energy of the frames: exp
+    /// (exponential weights), or a cumulant expansion to the second (cumulant2) or third order (cumulant3).
+    #[arg(long, value_enum)]
+    reweight: Option<analysis::Reweight>,
+    /// Column holding the boost energy (kcal/mol) for --reweight and --gamd-stats: GAMD_BOOST (read from gamd.log
+    /// files) or else the first column whose name contains BOOST by default (e.g. EAMD_BOOST).
+    #[arg(long, value_name = "COLUMN")]
+    boost: Option<String>,
+    /// Temperature of the reweighting in K, the mean of TEMP(K) by default.
+    #[arg(long, value_name = "K")]
+    reweight_temp: Option<f64>,
+    /// GaMD runs: print the mean, standard deviation, largest value and anharmonicity of the boost energy dV of each
+    /// run (window), written in LISFILES_GAMD.CSV, with the weights of the frames for PyReweighting (dV/kT, NSTEP, dV)
+    /// in LISFILES_GAMD_WEIGHTS.DAT.
+    #[arg(long)]
+    gamd_stats: bool,
+    /// Also write the forward and reverse cumulative means of each column against the time in LISFILES_CONVERGENCE.CSV
+    /// (and plot them with --gnuplot in convergence.gp): a run has converged when the two curves meet.
+    #[arg(long)]
+    convergence: bool,
+    /// Also write the share of each energy term (BOND, ANGLE, ..., EELEC) in the energy of each frame in
+    /// LISFILES_DECOMPOSITION.CSV (and plot them stacked with --gnuplot in decomposition.gp).
+    #[arg(long)]
+    decomposition: bool,
+    /// Runs at several temperatures, grouped by temp0 unless --group-by is given: write the mean and standard error
+    /// of each column of each run, sorted by temperature, with the heat capacity from the fluctuations of Etot,
+    /// in LISFILES_LADDER.CSV.
+    #[arg(long)]
+    ladder: bool,
+    /// Also write the table of each input file, or of each run (group) when several patterns are given, in its own
+    /// file in the LISFILES_PARTITIONS directory. With file, the run column holds the name of the file.
+    #[arg(long, value_enum)]
+    partition_by: Option<Partition>,
+    /// Format of the partitions.
+    #[arg(long, value_enum, default_value = "csv", requires = "partition_by")]
+    partition_format: PartitionFormat,
+    /// Only print the statistics and the reports, write no file (e.g. in a read-only directory).
+    #[arg(long, conflicts_with_all = ["gnuplot", "netcdf", "convergence", "decomposition", "partition_by", "cache", "resume", "time_limit", "append",
+        "force", "timestamped"])]
+    no_csv: bool,
+}
+
+// The rows written in each file with --partition-by.
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum Partition {
+    File,
+    Group,
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum PartitionFormat {
+    Csv,
+    Parquet,
+}
+
+// Parse the separator of the CSV columns given on the command line, "\t" (or "tab") being a tabulation.
+fn parse_delimiter(s: &str) -> Result<u8, String> {
+    match s {
+        "\\t" | "tab" => Ok(b'\t'),
+        _ if s.len() == 1 => Ok(s.as_bytes()[0]),
+        _ => Err(format!("the delimiter must be a single ASCII character, got \"{}\"", s)),
+    }
+}
+
+// Parse a duration given on the command line in ps, or with a unit: fs, ps, ns or us.
+fn parse_duration(s: &str) -> Result<f64, String> {
+    let s = s.trim();
+    let (number, factor) = [("fs", 1e-3), ("ps", 1.0), ("ns", 1e3), ("us", 1e6)]
+        .iter()
+        .find_map(|(unit, factor)| s.strip_suffix(unit).map(|n| (n, *factor)))
+        .unwrap_or((s, 1.0));
+    match number.trim().parse::<f64>() {
+        Ok(value) if value >= 0.0 => Ok(value * factor),
+        _ => Err(format!("expected a duration such as 5ns or 500ps, got \"{}\"", s)),
+    }
+}
+
+// Parse a size in bytes given on the command line, with an optional K, M or G suffix (powers of 1024).
+fn parse_size(s: &str) -> Result<usize, String> {
+    let upper = s.trim().to_uppercase();
+    let size = upper.strip_suffix('B').unwrap_or(&upper);
+    let (number, factor) = match size.chars().last() {
+        Some('K') => (&size[..size.len() - 1], 1 << 10),
+        Some('M') => (&size[..size.len() - 1], 1 << 20),
+        Some('G') => (&size[..size.len() - 1], 1 << 30),
+        _ => (size, 1),
+    };
+    // A size too large for the machine is refused as a malformed one.
+    match number.trim().parse::<usize>().ok().filter(|size| *size > 0).and_then(|size| size.checked_mul(factor)) {
+        Some(size) => Ok(size),
+        None => Err(format!("expected a size such as 64K or 8M, got \"{}\"", s)),
+    }
+}
+
+// Parse a fraction between 0 and 1 given on the command line.
+fn parse_fraction(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(value) if (0.0..1.0).contains(&value) => Ok(value),
+        _ => Err(format!("expected a fraction between 0 and 1, got \"{}\"", s)),
+    }
+}
+
+// Parse an "old=new" column renaming given on the command line.
+fn parse_rename(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((old, new)) if !old.trim().is_empty() && !new.trim().is_empty() => {
+            Ok((old.trim().to_string(), new.trim().to_string()))
+        }
+        _ => Err(format!("expected OLD=NEW, got \"{}\"", s)),
+    }
+}
+
+impl OutputArgs {
+    // The new name of each column to be renamed, from the TOML file and the command line.
+    fn renames(&self) -> Result<BTreeMap<String, String>, Box<dyn Error>> {
+        let mut renames = BTreeMap::new();
+        if let Some(file) = &self.rename_file {
+            let table: toml::Table = std::fs::read_to_string(file)?.parse()?;
+            for (old, new) in table {
+                let new = new.as_str().ok_or(format!("{}: the new name of \"{}\" is not a string", file.display(), old))?;
+                renames.insert(old, new.to_string());
+            }
+        }
+        renames.extend(self.rename.iter().cloned());
+        Ok(renames)
+    }
+
+    // The paths of the outputs written in dir, with the time of the run if asked. The existing files are only
+    // overwritten with --force, or with --resume which completes the outputs of an interrupted run.
+    fn paths(&self, dir: &Path) -> Result<OutputPaths, Box<dyn Error>> {
+        let suffix = if self.timestamped { format!("_{}", timestamp()) } else { String::new() };
+        let paths = OutputPaths {
+            csv: dir.join(format!("LISFILES_SUMMARY{}.CSV", suffix)),
+            netcdf: dir.join(format!("LISFILES_SUMMARY{}.nc", suffix)),
+            gnuplot: dir.join(format!("plot{}.gp", suffix)),
+            convergence: dir.join(format!("LISFILES_CONVERGENCE{}.CSV", suffix)),
+            convergence_gnuplot: dir.join(format!("convergence{}.gp", suffix)),
+            decomposition: dir.join(format!("LISFILES_DECOMPOSITION{}.CSV", suffix)),
+            decomposition_gnuplot: dir.join(format!("decomposition{}.gp", suffix)),
+            ladder: dir.join(format!("LISFILES_LADDER{}.CSV", suffix)),
+            gamd: dir.join(format!("LISFILES_GAMD{}.CSV", suffix)),
+            gamd_weights: dir.join(format!("LISFILES_GAMD_WEIGHTS{}.DAT", suffix)),
+            partitions: dir.join(format!("LISFILES_PARTITIONS{}", suffix)),
+        };
+        if !self.force && !self.resume && !self.append && !self.no_csv {
+            let written = [
+                (true, &paths.csv),
+                (self.netcdf, &paths.netcdf),
+                (self.gnuplot, &paths.gnuplot),
+                (self.convergence, &paths.convergence),
+                (self.convergence && self.gnuplot, &paths.convergence_gnuplot),
+                (self.decomposition, &paths.decomposition),
+                (self.decomposition && self.gnuplot, &paths.decomposition_gnuplot),
+                (self.ladder, &paths.ladder),
+                (self.gamd_stats, &paths.gamd),
+                (self.gamd_stats, &paths.gamd_weights),
+                (self.partition_by.is_some(), &paths.partitions),
+            ];
+            if let Some((_, existing)) = written.iter().find(|(write, path)| *write && path.exists()) {
+                return Err(format!("{} already exists, use --force to overwrite it or --timestamped to write new files",
+                    existing.display()).into());
+            }
+        }
+        Ok(paths)
+    }
+}
+
+// The files written with the table.
+struct OutputPaths {
+    csv: PathBuf,
+    netcdf: PathBuf,
+    gnuplot: PathBuf,
+    convergence: PathBuf,
+    convergence_gnuplot: PathBuf,
+    decomposition: PathBuf,
+    decomposition_gnuplot: PathBuf,
+    ladder: PathBuf,
+    gamd: PathBuf,
+    gamd_weights: PathBuf,
+    partitions: PathBuf,
+}
+
+// The current UTC date and time in the ISO 8601 basic format, usable in file names: 20261015T093000Z.
+fn timestamp() -> String {
+    let seconds = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, time) = (seconds / 86400, seconds % 86400);
+    // Civil date from the number of days since 1970-01-01 (proleptic Gregorian calendar).
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    format!("{:04}{:02}{:02}T{:02}{:02}{:02}Z", year, month, day, time / 3600, time % 3600 / 60, time % 60)
+}
+
+// The exit code of relis, for scripts and pipelines. Errors exit with 1 (and wrong arguments with 2, as for clap).
+#[derive(Clone, Copy)]
+enum Outcome {
+    Success = 0,
+    NoFilesMatched = 2,
+    NoDataExtracted = 3,
+    // Files skipped with --keep-going.
+    PartialFailure = 4,
+    // Warnings found in the files, with --strict.
+    WarningsFound = 5,
+}
 
 // A CLI tool that extracts values from AMBER MD .lis files.
-fn main() -> Result<(), Box<dyn Error>> {
+fn main() -> ExitCode {
+    // The errors are printed with their file and line, not as debug output.
+    match run() {
+        Ok(outcome) => ExitCode::from(outcome as u8),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> Result<Outcome, Box<dyn Error>> {
     // Arguments parsing.
-    let args = parse_args()?;
-    let pattern = args.0;
-    let path = PathBuf::from_str(&args.1)?;
-    extract_all_values(&pattern, &path)?;
-    Ok(())
+    let cli = Cli::parse();
+    match cli.command {
+        Some(Command::Watch(args)) => watch::watch(&args).map(|_| Outcome::Success),
+        Some(Command::Tui(args)) => tui::tui(&args).map(|_| Outcome::Success),
+        Some(Command::Serve(args)) => serve::serve(&args).map(|_| Outcome::Success),
+        Some(Command::Bench(args)) => bench::bench(&args).map(|_| Outcome::Success),
+        Some(Command::Fields) => {
+            fields::print_fields();
+            Ok(Outcome::Success)
+        }
+        Some(Command::Params(args)) => params::params(&args).map(|_| Outcome::Success),
+        Some(Command::Completions { shell }) => {
+            clap_complete::generate(shell, &mut Cli::command(), "relis", &mut std::io::stdout());
+            Ok(Outcome::Success)
+        }
+        None => extract_all_values(&cli.input.ok_or("No pattern provided")?, &cli.output),
+    }
 }
 
-fn extract_all_values(pattern: &String, path: &PathBuf) -> Result<(), Box<dyn Error>> {
-    println!("Searching pattern \"{}\" in directory {}", pattern, path.display());
-    let files = list_files(&path, &pattern)?;
-    println!("Files found: {}", files.len());
+fn extract_all_values(input: &InputArgs, output: &OutputArgs) -> Result<Outcome, Box<dyn Error>> {
+    if output.dry_run {
+        return dry_run::dry_run(input).map(|_| Outcome::Success);
+    }
+    let group = group_key(input, output)?;
+    let (path, pattern, files) = find_files(input)?;
+    // Checked before reading the files, which may take long.
+    let paths = output.paths(&path)?;
+    let existing = (output.append && paths.csv.exists()).then(|| paths.csv.clone());
+    let files = match &existing {
+        // The files not modified since the table was written are already in it.
+        Some(csv) if input.newer_than.is_none() && input.newer_than_file.is_none() => {
+            let files = modified::select(files, None, Some(modified::modified(csv)?))?;
+            println!("Files modified since {} was written: {}", csv.display(), files.len());
+            if files.is_empty() {
+                println!("Nothing to append");
+                return Ok(Outcome::Success);
+            }
+            files
+        }
+        _ => files,
+    };
     let mut df = DataFrame::new::<Series>(vec![])?;
-    for file in files {
-        println!("Reading file {}", file.display());
-        let lines = read_lines_until_pattern(&file, "RESULTS", "A V E R A G E");
-        let data = extract_values(&lines?)?;
-        let mut temp_df = DataFrame::new::<Series>(vec![])?;
-        // Iterate over the BTreeMap and create a new column for each key/values pair.
-        for (key, values) in data.iter() {
-            let s = Series::new(&key, values);
-            temp_df.with_column(s)?;
+    let bar = ProgressBar::new(files.len() as u64).with_style(ProgressStyle::with_template(
+        "{bar:40} {pos}/{len} files [{elapsed_precise} < {eta_precise}] {msg}",
+    )?);
+    let mut manifest = Manifest::new(&path, &pattern);
+    // The files read are always kept when the run may be resumed.
+    let cache = if output.cache || output.resume || output.time_limit.is_some() {
+        Some(cache::Cache::new(&path)?)
+    } else {
+        None
+    };
+    let options = input.options();
+    if let Some(cache) = &cache {
+        if output.resume {
+            match cache.checkpoint() {
+                Some(checkpoint) => println!("Resuming the run over {} ({} files)", checkpoint.pattern, checkpoint.files),
+                None => println!("No interrupted run to resume, reading all the files"),
+            }
+        }
+        cache.start_run(&pattern, files.len())?;
+    }
+    let start = Instant::now();
+    let mut bytes = 0;
+    let mut cached_files = 0;
+    let mut stopped_at = None;
+    let mut segments = Vec::new();
+    let mut deviations = Vec::new();
+    let mut sampling = Vec::new();
+    for (index, file) in files.iter().enumerate() {
+        if output.time_limit.is_some_and(|limit| start.elapsed().as_secs_f64() > limit) {
+            stopped_at = Some(index);
+            break;
+        }
+        bar.set_message(file.display().to_string());
+        let file_start = Instant::now();
+        let cached = cache.as_ref().and_then(|c| c.get(file, &options));
+        let from_cache = cached.is_some();
+        let FileData { engine, data, warnings, settings, bytes: file_bytes, invalid_lines, columns } = match cached {
+            Some(file_data) => file_data,
+            None => match read_file(file, input) {
+                Ok(file_data) => {
+                    if let Some(cache) = &cache {
+                        cache.put(file, &options, &file_data)?;
+                    }
+                    file_data
+                }
+                Err(e) => {
+                    fail(file, e, output.keep_going, &mut manifest, &bar)?;
+                    continue;
+                }
+            },
+        };
+        bytes += file_bytes;
+        cached_files += from_cache as usize;
+        manifest.warnings.extend(warnings.iter().map(|w| format!("{}: {}", file.display(), w)));
+        let frames = data.values().map(Vec::len).max().unwrap_or(0);
+        if data.is_empty() {
+            let reason = match engine {
+                Some(_) => "no data found",
+                None => "no data found, format not recognized",
+            };
+            manifest.skipped.push(SkippedFile { path: file.clone(), reason: reason.to_string() });
+        } else {
+            let mut file_df = to_dataframe(&data)?;
+            // The columns of the following files that are not in this one are added after them when stacking.
+            if !columns.is_empty() {
+                file_df = file_df.select(&columns)?;
+            }
+            if let Some(run) = group.as_ref().map(|key| group_name(file, key, &settings, input.patterns.len() > 1)) {
+                file_df.with_column(Series::new(RUN_COLUMN, vec![run; file_df.height()]))?;
+            }
+            df = match stack(&df, file_df) {
+                Ok(stacked) => stacked,
+                Err(e) => {
+                    let message = format!("the columns cannot be put together with those of the previous files: {}", e);
+                    fail(file, RelisError::Schema { path: Some(file.clone()), message }, output.keep_going, &mut manifest, &bar)?;
+                    continue;
+                }
+            };
+            if output.chain_check {
+                segments.extend(chain::Segment::new(file, &data));
+            }
+            if output.sampling_audit {
+                sampling.push((file.clone(), frames, analysis::output_interval(&data, &settings)));
+            }
+            if output.target_check {
+                deviations.extend(analysis::target_check(file, &data, &settings, output.temp_tolerance, output.press_tolerance));
+            }
+            manifest.files.push(ParsedFile {
+                path: file.clone(),
+                engine: engine.unwrap_or_default().name().to_string(),
+                frames,
+                columns: data.keys().cloned().collect(),
+                missing_columns: Vec::new(),
+            });
+        }
+        let report = format!("Read file {} ({} frames, {}{}) in {:.2?}", file.display(), frames,
+            engine.map_or("format not recognized", |e| e.name()), if from_cache { ", cached" } else { "" },
+            file_start.elapsed());
+        print_line(&bar, report);
+        if invalid_lines > 0 {
+            print_line(&bar, format!("    {} lines skipped as they are not text (binary data), the frames around them are kept",
+                invalid_lines));
+        }
+        bar.inc(1);
+    }
+    bar.finish_and_clear();
+    // Throughput report.
+    let elapsed = start.elapsed().as_secs_f64();
+    let read_files = stopped_at.unwrap_or(files.len());
+    println!("Read {} files ({:.1} MB, {} frames) in {:.2} s: {:.1} files/s, {:.1} MB/s",
+        read_files, bytes as f64 / 1e6, df.height(), elapsed,
+        read_files as f64 / elapsed, bytes as f64 / 1e6 / elapsed);
+    if !manifest.failed.is_empty() {
+        println!("Failed to read {} files:", manifest.failed.len());
+        for failed in &manifest.failed {
+            println!("    {}: {}", failed.path.display(), failed.error);
+        }
+    }
+    if let Some(cache) = &cache {
+        println!("{} files taken from the cache, {} parsed", cached_files, read_files - cached_files);
+        match stopped_at {
+            Some(index) => println!("Time limit reached after {} of {} files, run again with --resume to read the others",
+                index, files.len()),
+            None => cache.finish_run()?,
+        }
+    }
+    // The fields missing from some of the files are left empty in their rows.
+    let all_columns: Vec<String> = df.get_column_names().iter().filter(|c| **c != RUN_COLUMN).map(|c| c.to_string()).collect();
+    for parsed in &mut manifest.files {
+        parsed.missing_columns = all_columns.iter().filter(|c| !parsed.columns.contains(c)).cloned().collect();
+    }
+    let incomplete: Vec<&ParsedFile> = manifest.files.iter().filter(|f| !f.missing_columns.is_empty()).collect();
+    if !incomplete.is_empty() {
+        println!("{} files lack some of the fields, left empty in their rows:", incomplete.len());
+        for parsed in incomplete {
+            println!("    {}: {}", parsed.path.display(), parsed.missing_columns.join(", "));
+        }
+    }
+    for column in df.get_columns().iter().filter(|c| c.name() != RUN_COLUMN && c.null_count() > 0) {
+        manifest.missing_values.insert(column.name().to_string(), column.null_count());
+    }
+    if !manifest.missing_values.is_empty() {
+        let counts: Vec<String> = manifest.missing_values.iter().map(|(c, n)| format!("{} {}", c, n)).collect();
+        println!("Missing values (of {} frames): {}", df.height(), counts.join(", "));
+    }
+    if output.chain_check {
+        manifest.broken_chains = chain::check(segments);
+        if manifest.broken_chains.is_empty() {
+            println!("Chain check: the segments follow each other");
+        } else {
+            println!("Chain check: {} broken links, check the restart files used:", manifest.broken_chains.len());
+            for broken in &manifest.broken_chains {
+                println!("    {}", broken);
+            }
+        }
+    }
+    if output.target_check {
+        if deviations.is_empty() {
+            println!("Target check: the mean temperatures and pressures are within the tolerances of temp0 and pres0");
+        } else {
+            println!("Target check: {} deviations from the targets:", deviations.len());
+            for deviation in &deviations {
+                println!("    {}", deviation);
+            }
+        }
+        manifest.warnings.extend(deviations);
+    }
+    if output.sampling_audit {
+        println!("Sampling audit:");
+        for (file, frames, interval) in &sampling {
+            match interval {
+                Some(interval) => println!("    {}: {} frames every {} ps", file.display(), frames, interval),
+                None => println!("    {}: {} frames, interval unknown", file.display(), frames),
+            }
+        }
+        let mismatches = analysis::sampling_audit(&sampling);
+        if mismatches.is_empty() {
+            println!("The files are written at the same interval");
+        } else {
+            println!("{} files are written at a different interval, their frames weigh differently in the statistics:",
+                mismatches.len());
+            for mismatch in &mismatches {
+                println!("    {}", mismatch);
+            }
         }
-        df = df.vstack(&temp_df)?;
+        manifest.warnings.extend(mismatches);
     }
     // If there is nothing, exit.
     if df.is_empty() {
         println!("No data found.");
-        std::process::exit(0);
+        if let Some(manifest_path) = &output.manifest {
+            manifest.write(manifest_path)?;
+        }
+        return Ok(if files.is_empty() { Outcome::NoFilesMatched } else { Outcome::NoDataExtracted });
+    }
+    let mut df = build_table(df, input)?;
+    if let Some(csv) = &existing {
+        df = append_to(csv, df, output)?;
+    }
+    if output.coverage {
+        manifest.coverage = coverage_report(&df, &manifest.files)?;
+    }
+    if output.nve_report {
+        manifest.warnings.extend(analysis::nve_report(&df, output.drift_threshold)?);
+    }
+    if output.npt_report {
+        manifest.warnings.extend(analysis::npt_report(&df, output.npt_tolerance)?);
+    }
+    // Taken before the columns are renamed.
+    let production = production_rows(&df, output)?;
+    if output.ladder {
+        let production = production.as_ref().map_or_else(|| Ok(df.clone()), |rows| df.filter(rows))?;
+        let mut ladder = analysis::ladder(&production)?;
+        print_ladder(&ladder)?;
+        if !output.no_csv {
+            write_csv(&paths.ladder, &mut ladder, output)?;
+            println!("Temperature ladder saved in {}", paths.ladder.display());
+            manifest.outputs.push(paths.ladder.clone());
+        }
+    }
+    if output.gamd_stats {
+        let production = production.as_ref().map_or_else(|| Ok(df.clone()), |rows| df.filter(rows))?;
+        manifest.outputs.extend(gamd_outputs(&production, &paths, output)?);
+    }
+    manifest.outputs.extend(save_outputs(&mut df, &paths, output)?);
+    if let Some(manifest_path) = &output.manifest {
+        manifest.columns = df.get_column_names().iter().map(|c| c.to_string()).collect();
+        manifest.frames = df.height();
+        manifest.write(manifest_path)?;
+    }
+    print_summary(&df, production.as_ref(), output)?;
+    if !manifest.failed.is_empty() {
+        return Ok(Outcome::PartialFailure);
+    }
+    if output.strict && !manifest.warnings.is_empty() {
+        println!("{} warnings found in the files (--strict)", manifest.warnings.len());
+        return Ok(Outcome::WarningsFound);
+    }
+    Ok(Outcome::Success)
+}
+
+// Put the new rows of the table under those of the table written before in the CSV file: the rows after the last
+// time of their run in it. The columns renamed in the file are given back their name, to be renamed again.
+fn append_to(csv: &Path, df: DataFrame, output: &OutputArgs) -> Result<DataFrame, Box<dyn Error>> {
+    let mut existing = CsvReader::from_path(csv)?.has_header(true).with_delimiter(output.delimiter).finish()?;
+    for (old, new) in output.renames()? {
+        if existing.get_column_names().contains(&new.as_str()) {
+            existing.rename(&new, &old)?;
+        }
+    }
+    // The integers read from the file (e.g. NSTEP) are floats in the table, and the runs are names.
+    let names: Vec<String> = existing.get_column_names().iter().map(|c| c.to_string()).collect();
+    for name in names {
+        let dtype = if name == RUN_COLUMN { DataType::Utf8 } else { DataType::Float64 };
+        let column = existing.column(&name)?.cast(&dtype)?;
+        existing.with_column(column)?;
+    }
+    let time = time_column(&df.get_column_names())
+        .filter(|t| existing.column(t).is_ok())
+        .ok_or("--append needs a time column in the table and in the file")?;
+    let last_times: BTreeMap<Option<String>, f64> = analysis::runs(&existing)?
+        .into_iter()
+        .map(|(run, rows)| Ok((run, analysis::values(&rows, &time)?.into_iter().fold(f64::NEG_INFINITY, f64::max))))
+        .collect::<Result<_, Box<dyn Error>>>()?;
+    let runs: Vec<Option<&str>> = match df.column(RUN_COLUMN) {
+        Ok(run) => run.utf8()?.into_iter().collect(),
+        Err(_) => vec![None; df.height()],
+    };
+    let times: Vec<Option<f64>> = df.column(&time)?.cast(&DataType::Float64)?.f64()?.into_iter().collect();
+    let new: BooleanChunked = runs
+        .iter()
+        .zip(&times)
+        .map(|(run, t)| {
+            let last = last_times.get(&run.map(String::from)).copied().unwrap_or(f64::NEG_INFINITY);
+            t.is_some_and(|t| t > last)
+        })
+        .collect();
+    let df = df.filter(&new)?;
+    println!("Appending {} new frames to the {} frames of {}", df.height(), existing.height(), csv.display());
+    Ok(stack(&existing, df)?)
+}
+
+// Put the rows of a file under the table, the columns missing from one of them are added to it with null values.
+fn stack(df: &DataFrame, mut file_df: DataFrame) -> Result<DataFrame, RelisError> {
+    if df.width() == 0 {
+        return Ok(file_df);
+    }
+    let mut df = df.clone();
+    for column in df.get_columns() {
+        if !file_df.get_column_names().contains(&column.name()) {
+            file_df.with_column(Series::full_null(column.name(), file_df.height(), column.dtype()))?;
+        }
+    }
+    for column in file_df.get_columns() {
+        if !df.get_column_names().contains(&column.name()) {
+            df.with_column(Series::full_null(column.name(), df.height(), column.dtype()))?;
+        }
+    }
+    Ok(df.vstack(&file_df.select(df.get_column_names())?)?)
+}
+
+// Record a file that could not be read with --keep-going, or stop at the first one without it.
+fn fail(file: &Path, e: RelisError, keep_going: bool, manifest: &mut Manifest, bar: &ProgressBar) -> Result<(), RelisError> {
+    if !keep_going {
+        return Err(e);
+    }
+    print_line(bar, format!("Failed to read file {}, skipped", file.display()));
+    bar.inc(1);
+    manifest.failed.push(FailedFile { path: file.to_path_buf(), error: e.to_string() });
+    Ok(())
+}
+
+// Print a line above the progress bar.
+fn print_line(bar: &ProgressBar, line: String) {
+    // The bar is hidden when the output is not a terminal, and so are its messages.
+    if bar.is_hidden() {
+        println!("{}", line);
+    } else {
+        bar.println(line);
+    }
+}
+
+// The values read from a file.
+struct FileData {
+    engine: Option<Engine>,
+    data: BTreeMap<String, Vec<f64>>,
+    // The lines reporting problems in the simulation.
+    warnings: Vec<String>,
+    // The numeric settings echoed before the values of AMBER files (e.g. temp0, ntt), with lowercase names.
+    settings: BTreeMap<String, f64>,
+    // Number of bytes read.
+    bytes: u64,
+    // Number of lines skipped as they are not text.
+    invalid_lines: usize,
+    // Names of the fields in the order in which they first appear, empty for the entries of older caches.
+    columns: Vec<String>,
+}
+
+// Regex capturing the settings echoed at the beginning of AMBER files, e.g. "temp0   = 300.00000".
+const SETTING_REGEX: &str = r"\b([A-Za-z_][A-Za-z0-9_]*)\s*=\s*(-?\d+\.?\d*(?:[eE][-+]?\d+)?)";
+
+// Add the settings of a line to those already read, with lowercase names. The first value of each one is kept.
+fn read_settings(line: &str, setting_re: &Regex, settings: &mut BTreeMap<String, f64>) {
+    for cap in setting_re.captures_iter(line) {
+        if let Ok(value) = cap[2].parse() {
+            settings.entry(cap[1].to_lowercase()).or_insert(value);
+        }
     }
-    // Check if a column named "TIME(PS)" exists. 
-    // If true, put it in first position and sort the values in ascending time order.
+}
+
+// Read the values of a file, local or remote. The errors give the path of the file.
+fn read_file(file: &Path, input: &InputArgs) -> Result<FileData, RelisError> {
+    read_values(file, input).map_err(|e| e.in_file(file))
+}
+
+fn read_values(file: &Path, input: &InputArgs) -> Result<FileData, RelisError> {
+    let (mut file_data, bytes, invalid) = match input.map(file)? {
+        Some(map) => {
+            let engine = input.engine(&map[..map.len().min(relis::DETECTION_BYTES)]);
+            let mut lines = ByteLines::new(&map);
+            let file_data = read_lines(engine, input, lines.by_ref().map(Ok))?;
+            (file_data, lines.bytes, lines.invalid)
+        }
+        None => {
+            let OpenedFile { engine, reader } = input.open(file)?;
+            let mut lines = Lines::new(reader);
+            let file_data = read_lines(engine, input, lines.by_ref())?;
+            (file_data, lines.bytes, lines.invalid)
+        }
+    };
+    file_data.bytes = bytes;
+    file_data.invalid_lines = invalid;
+    if invalid > 0 {
+        file_data.warnings.push(format!("{} lines skipped as they are not text (binary data)", invalid));
+    }
+    Ok(file_data)
+}
+
+// Read the values of the lines of a file written by engine (AMBER if not known), up to the end of the values.
+fn read_lines<S: AsRef<str>>(engine: Option<Engine>, input: &InputArgs, lines: impl Iterator<Item = std::io::Result<S>>)
+    -> Result<FileData, RelisError> {
+    let mut extractor = input.extractor(engine.unwrap_or_default());
+    let mut file_data = FileData { engine, data: BTreeMap::new(), warnings: Vec::new(), settings: BTreeMap::new(), bytes: 0,
+        invalid_lines: 0, columns: Vec::new() };
+    let setting_re = Regex::new(SETTING_REGEX)?;
+    let amber = matches!(extractor.engine(), Engine::Amber | Engine::AmberMin);
+    for line in lines {
+        let line = line?;
+        let line = line.as_ref();
+        let read = extractor.line(line, &mut file_data.data)?;
+        if file_data.data.len() > file_data.columns.len() {
+            record_columns(line, &file_data.data, &mut file_data.columns);
+        }
+        if read && is_warning(line) {
+            file_data.warnings.push(line.trim().to_string());
+        }
+        // The settings are echoed before the values, the first value of each one is kept.
+        if amber && !read && file_data.data.is_empty() {
+            read_settings(line, &setting_re, &mut file_data.settings);
+        }
+        if extractor.ended() {
+            break;
+        }
+    }
+    extractor.finish(&mut file_data.data);
+    record_columns("", &file_data.data, &mut file_data.columns);
+    Ok(file_data)
+}
+
+// Add the fields that have just appeared to the column order, in the order of their names in the line (those not
+// found in it, e.g. renamed, after them).
+fn record_columns(line: &str, data: &BTreeMap<String, Vec<f64>>, columns: &mut Vec<String>) {
+    let mut new: Vec<&String> = data.keys().filter(|k| !columns.contains(k)).collect();
+    new.sort_by_key(|k| line.find(k.as_str()).unwrap_or(usize::MAX));
+    columns.extend(new.into_iter().cloned());
+}
+
+// The table written from the values read: ordered, with the selected fields, the cpptraj columns, the joined tables
+// and the derived columns.
+fn build_table(df: DataFrame, input: &InputArgs) -> Result<DataFrame, RelisError> {
+    let df = select_fields(order_table(df, input)?, input)?;
+    let df = join::join_cpptraj(df, &input.cpptraj)?;
+    let df = join::join_tables(df, &input.join, input.on.as_deref())?;
+    derive::add_columns(df, &input.derive)
+}
+
+// Keep the fields given with --fields and --preset, with the run and the time. The fields not found are left out.
+fn select_fields(df: DataFrame, input: &InputArgs) -> Result<DataFrame, RelisError> {
+    if (input.fields.is_empty() && input.preset.is_empty()) || df.is_empty() {
+        return Ok(df);
+    }
+    let columns = df.get_column_names();
+    let presets = input.preset.iter().flat_map(|p| p.fields().iter().copied());
+    let selected: Vec<&str> = input.fields.iter().map(String::as_str).chain(presets).collect();
+    let time = time_column(&columns);
+    let kept: Vec<&str> = columns
+        .iter()
+        .copied()
+        .filter(|c| *c == RUN_COLUMN || Some(*c) == time.as_deref() || selected.contains(c))
+        .collect();
+    Ok(df.select(kept)?)
+}
+
+// Check if a time column exists, if true, put it in first position (after the run column if any), with the other
+// columns in the order given with --column-order. Then sort the values in ascending time order, or as asked on the
+// command line, run by run. The sort is stable: the frames with the same key stay in the order they were read.
+fn order_table(mut df: DataFrame, input: &InputArgs) -> Result<DataFrame, RelisError> {
     let mut col = df.get_column_names();
-    if let Some(pos) = &col.iter().position(|x| *x == "TIME(PS)") {
-        col.remove(*pos);
-        col.insert(0, "TIME(PS)");
-        df = df.select(&col)?;
-        df.sort_in_place(["TIME(PS)"], false)?;
-    }
-    let csv_path = path.join("LISFILES_SUMMARY.CSV");
-    let mut csv_file = std::fs::File::create(&csv_path)?;
-    CsvWriter::new(&mut csv_file).finish(&mut df)?;
+    match input.column_order {
+        // The table has the columns in the order they were read.
+        fields::ColumnOrder::Input => {}
+        fields::ColumnOrder::Alpha => col.sort_unstable(),
+        fields::ColumnOrder::Preset => {
+            let presets = input.preset.iter().flat_map(|p| p.fields().iter().copied());
+            let mut given: Vec<&str> = input.fields.iter().map(String::as_str).chain(presets).collect();
+            if given.is_empty() {
+                given = fields::KNOWN_FIELDS.to_vec();
+            }
+            col.sort_by_key(|c| (given.iter().position(|g| g == c).unwrap_or(usize::MAX), *c));
+        }
+    }
+    let time = time_column(&col);
+    if let Some(time) = &time {
+        col.retain(|x| x != time);
+        col.insert(0, time);
+    }
+    let runs = col.contains(&RUN_COLUMN);
+    if runs {
+        col.retain(|x| *x != RUN_COLUMN);
+        col.insert(0, RUN_COLUMN);
+    }
+    df = df.select(&col)?;
+    if input.no_sort {
+        return Ok(df);
+    }
+    let (column, descending) = match (&input.sort_by, &time) {
+        (Some(key), _) => {
+            if !df.get_column_names().contains(&key.column.as_str()) {
+                return Err(RelisError::schema(format!("cannot sort by \"{}\": no such column", key.column)));
+            }
+            (key.column.as_str(), key.descending)
+        }
+        (None, Some(time)) => (time.as_str(), false),
+        (None, None) if runs => (RUN_COLUMN, false),
+        (None, None) => return Ok(df),
+    };
+    // The row numbers break the ties, as the sort of polars does not keep the order of equal keys.
+    let mut df = df.with_row_count(ROW_COLUMN, None)?;
+    if runs && column != RUN_COLUMN {
+        df.sort_in_place([RUN_COLUMN, column, ROW_COLUMN], vec![false, descending, false])?;
+    } else {
+        df.sort_in_place([column, ROW_COLUMN], vec![descending, false])?;
+    }
+    Ok(df.drop(ROW_COLUMN)?)
+}
+
+// Rename the columns, write the CSV file (and the gnuplot script if asked) in the search directory.
+// Returns the paths of the files written.
+fn save_outputs(df: &mut DataFrame, paths: &OutputPaths, output: &OutputArgs) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let time = time_column(&df.get_column_names());
+    // The units are known from the names given by the programs.
+    let units: Vec<Option<&str>> = df.get_column_names().iter().map(|c| fields::unit(c)).collect();
+    let descriptions: Vec<Option<&str>> = df.get_column_names().iter().map(|c| fields::description(c)).collect();
+    let renames = output.renames()?;
+    for (old, new) in &renames {
+        if df.get_column_names().contains(&old.as_str()) {
+            df.rename(old, new)?;
+        }
+    }
+    // The time column may have been renamed.
+    let time = time.map(|t| renames.get(&t).cloned().unwrap_or(t));
+    let time = time.as_deref();
+    if output.no_csv {
+        return Ok(Vec::new());
+    }
+    let csv_path = paths.csv.clone();
+    if output.long {
+        write_csv(&csv_path, &mut to_long(df, time)?, output)?;
+    } else {
+        write_csv(&csv_path, df, output)?;
+    }
     println!("Data saved in {}", csv_path.display());
-    // Print the mean and standard deviation for each column in the terminal.
+    let mut outputs = vec![csv_path.clone()];
+    if output.netcdf {
+        let nc_path = paths.netcdf.clone();
+        netcdf::write(&nc_path, df, time, &units, &descriptions)?;
+        println!("NetCDF file saved in {}", nc_path.display());
+        outputs.push(nc_path);
+    }
+    if output.gnuplot {
+        let gp_path = paths.gnuplot.clone();
+        let layout = if output.long {
+            gnuplot::Layout::Long { time: time.unwrap_or(FRAME_COLUMN) }
+        } else {
+            gnuplot::Layout::Wide { time }
+        };
+        let columns: Vec<&str> = df.get_column_names().into_iter().filter(|c| *c != RUN_COLUMN).collect();
+        gnuplot::write_script(&gp_path, &csv_path, &columns, layout, output.delimiter)?;
+        println!("Gnuplot script saved in {}", gp_path.display());
+        outputs.push(gp_path);
+    }
+    if output.convergence {
+        let mut convergence = analysis::convergence(df, time)?;
+        write_csv(&paths.convergence, &mut convergence, output)?;
+        println!("Cumulative means saved in {}", paths.convergence.display());
+        outputs.push(paths.convergence.clone());
+        if output.gnuplot {
+            let columns: Vec<&str> = df.get_column_names().into_iter().filter(|c| *c != RUN_COLUMN).collect();
+            let layout = gnuplot::Layout::Convergence { time: time.unwrap_or(FRAME_COLUMN) };
+            gnuplot::write_script(&paths.convergence_gnuplot, &paths.convergence, &columns, layout, output.delimiter)?;
+            println!("Gnuplot script saved in {}", paths.convergence_gnuplot.display());
+            outputs.push(paths.convergence_gnuplot.clone());
+        }
+    }
+    if output.decomposition {
+        match analysis::decomposition(df, time)? {
+            Some(mut decomposition) => {
+                write_csv(&paths.decomposition, &mut decomposition, output)?;
+                println!("Energy decomposition saved in {}", paths.decomposition.display());
+                outputs.push(paths.decomposition.clone());
+                if output.gnuplot {
+                    let columns = decomposition.get_column_names();
+                    let columns: Vec<&str> = columns.into_iter().filter(|c| *c != RUN_COLUMN).collect();
+                    let layout = gnuplot::Layout::Stacked { time: time.unwrap_or(FRAME_COLUMN) };
+                    gnuplot::write_script(&paths.decomposition_gnuplot, &paths.decomposition, &columns, layout, output.delimiter)?;
+                    println!("Gnuplot script saved in {}", paths.decomposition_gnuplot.display());
+                    outputs.push(paths.decomposition_gnuplot.clone());
+                }
+            }
+            None => println!("Energy decomposition: less than two energy terms in the table, nothing written"),
+        }
+    }
+    if output.partition_by.is_some() {
+        let written = write_partitions(df, &paths.partitions, output)?;
+        println!("{} partitions saved in {}", written.len(), paths.partitions.display());
+        outputs.extend(written);
+    }
+    Ok(outputs)
+}
+
+// Write the table of each run in its own file of dir, named after the run, without the run column.
+fn write_partitions(df: &DataFrame, dir: &Path, output: &OutputArgs) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    if df.column(RUN_COLUMN).is_err() {
+        return Err("--partition-by group needs several runs: several patterns, or --group-by".into());
+    }
+    std::fs::create_dir_all(dir)?;
+    let extension = match output.partition_format {
+        PartitionFormat::Csv => "csv",
+        PartitionFormat::Parquet => "parquet",
+    };
+    let mut written = Vec::new();
+    for (run, partition) in analysis::runs(df)? {
+        let name: String = run.unwrap_or_default().chars().map(|c| if c == '/' || c == '\\' { '_' } else { c }).collect();
+        let path = dir.join(format!("{}.{}", name, extension));
+        let mut partition = partition.drop(RUN_COLUMN)?;
+        match output.partition_format {
+            PartitionFormat::Csv => write_csv(&path, &mut partition, output)?,
+            PartitionFormat::Parquet => {
+                ParquetWriter::new(std::fs::File::create(&path)?).finish(&mut partition)?;
+            }
+        }
+        written.push(path);
+    }
+    Ok(written)
+}
+
+// Write a table in a CSV file, with the format asked on the command line.
+fn write_csv(path: &Path, df: &mut DataFrame, output: &OutputArgs) -> Result<(), Box<dyn Error>> {
+    let mut file = std::fs::File::create(path)?;
+    CsvWriter::new(&mut file)
+        .has_header(!output.no_header)
+        .with_delimiter(output.delimiter)
+        .with_float_precision(output.precision)
+        .finish(df)?;
+    Ok(())
+}
+
+// Name of the column numbering the frames in the long format, when there is no time column.
+const FRAME_COLUMN: &str = "frame";
+
+// Name of the column holding the run of each frame, when several patterns are given.
+const RUN_COLUMN: &str = "run";
+
+// Name of the column of row numbers added while sorting.
+const ROW_COLUMN: &str = "relis_row";
+
+// Turn the table into a tidy table with the columns (time, variable, value), sorted by variable and time.
+// The frames are numbered in a "frame" column if there is no time column. Missing values are left out.
+fn to_long(df: &DataFrame, time: Option<&str>) -> Result<DataFrame, Box<dyn Error>> {
+    let (df, time) = match time {
+        Some(time) => (df.clone(), time),
+        None => (df.with_row_count(FRAME_COLUMN, None)?, FRAME_COLUMN),
+    };
+    // The run column, if any, is kept on each row.
+    let ids: Vec<&str> = [RUN_COLUMN, time].into_iter().filter(|c| df.get_column_names().contains(c)).collect();
+    let values: Vec<&str> = df.get_column_names().into_iter().filter(|c| !ids.contains(c)).collect();
+    let long = df.melt(&ids, values)?;
+    let long = long.drop_nulls(Some(&["value".to_string()]))?;
+    let mut order = vec!["variable"];
+    order.extend(&ids);
+    Ok(long.sort(order, false)?)
+}
+
+// The rows left after the beginning of each run skipped with --skip or --skip-frac, None if nothing is skipped.
+fn production_rows(df: &DataFrame, output: &OutputArgs) -> Result<Option<BooleanChunked>, Box<dyn Error>> {
+    if output.skip.is_none() && output.skip_frac.is_none() {
+        return Ok(None);
+    }
+    let runs: Vec<Option<&str>> = match df.column(RUN_COLUMN) {
+        Ok(run) => run.utf8()?.into_iter().collect(),
+        Err(_) => vec![None; df.height()],
+    };
+    let mut rows = vec![true; df.height()];
+    if let Some(skip) = output.skip {
+        let time = time_column(&df.get_column_names()).ok_or("--skip needs a time column, use --skip-frac instead")?;
+        let times: Vec<Option<f64>> = df.column(&time)?.cast(&DataType::Float64)?.f64()?.into_iter().collect();
+        // The beginning of each run.
+        let mut starts: BTreeMap<Option<&str>, f64> = BTreeMap::new();
+        for (run, time) in runs.iter().zip(&times) {
+            if let Some(time) = time {
+                let start = starts.entry(*run).or_insert(*time);
+                *start = start.min(*time);
+            }
+        }
+        for (row, (run, time)) in rows.iter_mut().zip(runs.iter().zip(&times)) {
+            *row = time.is_some_and(|t| t >= starts[run] + skip);
+        }
+    }
+    if let Some(fraction) = output.skip_frac {
+        let mut frames: BTreeMap<Option<&str>, usize> = BTreeMap::new();
+        for run in &runs {
+            *frames.entry(*run).or_default() += 1;
+        }
+        let mut seen: BTreeMap<Option<&str>, usize> = BTreeMap::new();
+        for (row, run) in rows.iter_mut().zip(&runs) {
+            let index = seen.entry(*run).or_default();
+            *row = (*index as f64) >= fraction * frames[run] as f64;
+            *index += 1;
+        }
+    }
+    Ok(Some(BooleanChunked::from_slice("production", &rows)))
+}
+
+// Print the mean and standard deviation for each column in the terminal, over the rows given if any,
+// with the bootstrap confidence interval of the mean if a number of resamples is given.
+fn print_summary(df: &DataFrame, rows: Option<&BooleanChunked>, output: &OutputArgs) -> Result<(), Box<dyn Error>> {
+    let df = match rows {
+        Some(rows) => {
+            let production = df.filter(rows)?;
+            println!("Statistics over {} of {} frames, after the skipped equilibration", production.height(), df.height());
+            production
+        }
+        None => df.clone(),
+    };
+    let reweight = match output.reweight {
+        Some(method) => {
+            let boost = boost_column(&df, output)?;
+            let temperature = reweight_temperature(&df, output)?
+                .ok_or("--reweight needs a temperature, give it with --reweight-temp")?;
+            println!("Means reweighted by the boost energy {} at {} K", boost, temperature);
+            Some((boost, temperature, method))
+        }
+        None => None,
+    };
+    if output.replicates && df.column(RUN_COLUMN).is_err() {
+        return Err("--replicates needs several runs: several patterns, or --group-by".into());
+    }
+    let replicates = if output.replicates { Some(analysis::runs(&df)?) } else { None };
+    for stats in summary_stats(&df)? {
+        let ci = match output.bootstrap {
+            Some(resamples) => analysis::bootstrap_ci(&analysis::values(&df, &stats.name)?, resamples),
+            None => None,
+        };
+        let mut lines = format!("          {}\n\nMean=     {}\nStd=      {}\n", stats.name, stats.mean, stats.std);
+        if let Some((low, high)) = ci {
+            lines += &format!("CI95=     [{}, {}]\n", low, high);
+        }
+        if let Some((boost, temperature, method)) = &reweight {
+            let pairs = analysis::pairs(&df, &stats.name, boost)?;
+            lines += &format!("Reweighted= {}\n", analysis::reweighted_mean(&pairs, *temperature, *method));
+        }
+        if let Some(replicates) = &replicates {
+            let (mean, error, n) = analysis::replicate_mean(replicates, &stats.name)?;
+            lines += &format!("Replicates= {} +/- {} ({} replicates)\n", mean, error, n);
+        }
+        println!("{}------------------------------", lines);
+    }
+    Ok(())
+}
+
+// Print where each column has values, and the files without it.
+fn coverage_report(df: &DataFrame, files: &[ParsedFile]) -> Result<Vec<manifest::ColumnCoverage>, Box<dyn Error>> {
+    let time = time_column(&df.get_column_names());
+    let mut coverage = analysis::coverage(df, time.as_deref())?;
+    println!("Coverage of the columns (of {} frames):", df.height());
+    let show = |v: Option<f64>| v.map_or("-".to_string(), |v| v.to_string());
+    for column in &mut coverage {
+        column.absent_from = files
+            .iter()
+            .filter(|f| f.missing_columns.contains(&column.column))
+            .map(|f| f.path.clone())
+            .collect();
+        let mut line = format!("    {}: {:.1}% of the frames, {} {} to {}", column.column, 100.0 * column.fraction,
+            time.as_deref().unwrap_or("frames"), show(column.first), show(column.last));
+        if !column.absent_from.is_empty() {
+            let shown: Vec<String> = column.absent_from.iter().take(3).map(|p| p.display().to_string()).collect();
+            let more = if column.absent_from.len() > shown.len() { ", ..." } else { "" };
+            line += &format!(", absent from {} files: {}{}", column.absent_from.len(), shown.join(", "), more);
+        }
+        println!("{}", line);
+    }
+    Ok(coverage)
+}
+
+// The column of the boost energy with which the frames are reweighted.
+fn boost_column(df: &DataFrame, output: &OutputArgs) -> Result<String, Box<dyn Error>> {
+    let columns = df.get_column_names();
+    match &output.boost {
+        Some(boost) if df.column(boost).is_err() => Err(format!("--boost: no column \"{}\"", boost).into()),
+        Some(boost) => Ok(boost.clone()),
+        // The boost energies of gamd.log contain "Boost" too, their sum is the one wanted.
+        None => Ok(columns
+            .iter()
+            .find(|c| **c == relis::gamd::BOOST_COLUMN)
+            .or_else(|| columns.iter().find(|c| c.to_uppercase().contains("BOOST")))
+            .ok_or("the boost energy of the frames is needed, give its column with --boost")?
+            .to_string()),
+    }
+}
+
+// The temperature of the reweighting, None if it is neither given nor in the table.
+fn reweight_temperature(df: &DataFrame, output: &OutputArgs) -> Result<Option<f64>, Box<dyn Error>> {
+    Ok(match output.reweight_temp {
+        Some(temperature) => Some(temperature),
+        None if df.column("TEMP(K)").is_ok() => Some(analysis::mean(&analysis::values(df, "TEMP(K)")?)),
+        None => None,
+    })
+}
+
+// Print the statistics of the boost energy of each GaMD run and write them, with the weights of the frames for
+// PyReweighting. Returns the paths of the files written.
+fn gamd_outputs(df: &DataFrame, paths: &OutputPaths, output: &OutputArgs) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let boost = boost_column(df, output)?;
+    let mut stats = analysis::gamd_stats(df, &boost)?;
+    println!("Boost energy {} (kcal/mol):", boost);
+    let column = |name: &str| -> Result<Vec<Option<f64>>, Box<dyn Error>> {
+        Ok(stats.column(name)?.f64()?.into_iter().collect())
+    };
+    let (means, stds) = (column("dV_mean")?, column("dV_std")?);
+    let (maxes, anharmonicities) = (column("dV_max")?, column("anharmonicity")?);
+    let runs: Vec<Option<&str>> = match stats.column(RUN_COLUMN) {
+        Ok(runs) => runs.utf8()?.into_iter().collect(),
+        Err(_) => vec![None; stats.height()],
+    };
+    let show = |v: Option<f64>| v.map_or("-".to_string(), |v| format!("{:.4}", v));
+    for (i, run) in runs.iter().enumerate() {
+        let name = run.map_or(String::new(), |r| format!("{}: ", r));
+        println!("    {}mean = {}, std = {}, max = {}, anharmonicity = {}", name, show(means[i]), show(stds[i]),
+            show(maxes[i]), show(anharmonicities[i]));
+    }
+    if output.no_csv {
+        return Ok(Vec::new());
+    }
+    write_csv(&paths.gamd, &mut stats, output)?;
+    println!("Boost statistics saved in {}", paths.gamd.display());
+    let mut written = vec![paths.gamd.clone()];
+    match reweight_temperature(df, output)? {
+        Some(temperature) => {
+            write_gamd_weights(&paths.gamd_weights, df, &boost, temperature)?;
+            println!("Weights of the frames at {} K saved in {}", temperature, paths.gamd_weights.display());
+            written.push(paths.gamd_weights.clone());
+        }
+        None => println!("No temperature to write the weights of the frames, give it with --reweight-temp"),
+    }
+    Ok(written)
+}
+
+// Write the weights file read by PyReweighting: dV/kT, the step (or the number of the frame) and dV, one line per
+// frame in the order of the table.
+fn write_gamd_weights(path: &Path, df: &DataFrame, boost: &str, temperature: f64) -> Result<(), Box<dyn Error>> {
+    let kt = analysis::KB * temperature;
+    let boosts = df.column(boost)?.cast(&DataType::Float64)?;
+    let steps = match df.column("NSTEP") {
+        Ok(steps) => steps.cast(&DataType::Float64)?,
+        Err(_) => Series::new("frame", (1..=df.height()).map(|i| i as f64).collect::<Vec<_>>()),
+    };
+    let mut text = String::new();
+    for (dv, step) in boosts.f64()?.into_iter().zip(steps.f64()?) {
+        let dv = dv.unwrap_or(f64::NAN);
+        text += &format!("{} {} {}\n", dv / kt, step.unwrap_or(f64::NAN), dv);
+    }
+    std::fs::write(path, text).map_err(|e| RelisError::from(e).in_file(path))?;
+    Ok(())
+}
+
+// Mean and standard deviation of a column, NaN when a value can't be computed.
+struct ColumnStats {
+    name: String,
+    mean: f64,
+    std: f64,
+}
+
+// Compute the mean and standard deviation of each column.
+fn summary_stats(df: &DataFrame) -> Result<Vec<ColumnStats>, Box<dyn Error>> {
     let mut summary = df.mean();
     summary = summary.vstack(&df.std(0))?;
-    for col in summary.get_column_names() {
-        println!("          {}\n\nMean=     {}\nStd=      {}\n------------------------------",
-        col,
-        summary.column(col).unwrap().f64().unwrap().get(0).unwrap(),
-        summary.column(col).unwrap().f64().unwrap().get(1).unwrap());
+    let mut stats = Vec::new();
+    // The run column has no statistics.
+    for col in summary.get_columns().iter().filter(|c| c.name() != RUN_COLUMN) {
+        let values = col.cast(&DataType::Float64)?;
+        let values = values.f64()?;
+        stats.push(ColumnStats {
+            name: col.name().to_string(),
+            mean: values.get(0).unwrap_or(f64::NAN),
+            std: values.get(1).unwrap_or(f64::NAN),
+        });
+    }
+    Ok(stats)
+}
+
+// Return the files to read, with the directory in which the outputs are written and the patterns
+// (or the list of files) for the messages and the manifest.
+fn find_files(input: &InputArgs) -> Result<(PathBuf, String, Vec<PathBuf>), RelisError> {
+    let (path, pattern, files) = match_files(input)?;
+    let count = files.len();
+    let files = input.select_files(files)?;
+    if files.len() < count {
+        println!("Files modified after the given date: {}", files.len());
+    }
+    Ok((path, pattern, files))
+}
+
+fn match_files(input: &InputArgs) -> Result<(PathBuf, String, Vec<PathBuf>), RelisError> {
+    if let Some(list) = &input.files_from {
+        println!("Reading the list of files from {}", list.display());
+        let files = read_file_list(list)?;
+        println!("Files found: {}", files.len());
+        return Ok((std::env::current_dir()?, format!("--files-from {}", list.display()), files));
+    }
+    match input.patterns.as_slice() {
+        [] => Err(RelisError::Glob { pattern: String::new(), message: "no pattern provided".to_string() }),
+        [pattern] => find_pattern_files(pattern, input.literal),
+        patterns => {
+            let mut files = Vec::new();
+            for pattern in patterns {
+                files.extend(find_pattern_files(pattern, input.literal)?.2);
+            }
+            Ok((std::env::current_dir()?, patterns.join(" "), files))
+        }
+    }
+}
+
+fn find_pattern_files(pattern: &str, literal: bool) -> Result<(PathBuf, String, Vec<PathBuf>), RelisError> {
+    if remote::is_remote(pattern) {
+        println!("Listing remote files {}", pattern);
+        let files = remote::list(pattern)?;
+        println!("Files found: {}", files.len());
+        return Ok((std::env::current_dir()?, pattern.to_string(), files));
+    }
+    if archive::is_archive(pattern) {
+        println!("Listing the files of archive {}", pattern);
+        let files = archive::list(pattern)?;
+        println!("Files found: {}", files.len());
+        // The outputs are written next to the archive.
+        let (archive, _) = archive::split(pattern)?;
+        let dir = archive.parent().filter(|d| !d.as_os_str().is_empty()).map_or(std::env::current_dir()?, Path::to_path_buf);
+        return Ok((dir, pattern.to_string(), files));
+    }
+    let file = Path::new(pattern);
+    if literal || file.is_file() {
+        if !file.is_file() {
+            return Err(RelisError::io(file, std::io::ErrorKind::NotFound, "no such file"));
+        }
+        println!("Reading file {}", file.display());
+        let dir = file.parent().filter(|d| !d.as_os_str().is_empty()).map_or(std::env::current_dir()?, Path::to_path_buf);
+        return Ok((dir, pattern.to_string(), vec![file.to_path_buf()]));
+    }
+    let (pattern, path) = parse_args(pattern)?;
+    println!("Searching pattern \"{}\" in directory {}", pattern, path.display());
+    let files = list_files(&path, &pattern)?;
+    println!("Files found: {}", files.len());
+    Ok((path, pattern, files))
+}
+
+// The run of a file when several patterns are given: the name of its directory, or of its archive.
+fn run_name(file: &Path) -> String {
+    let name = file.to_string_lossy();
+    if archive::is_archive(&name) {
+        if let Ok((archive, _)) = archive::split(&name) {
+            let name = archive.file_name().unwrap_or_default().to_string_lossy().to_string();
+            return [".tar.gz", ".tgz", ".tar"].iter().find_map(|ext| name.strip_suffix(ext)).unwrap_or(&name).to_string();
+        }
+    }
+    let dir = if remote::is_remote(&name) {
+        file.parent().map(Path::to_path_buf)
+    } else {
+        std::fs::canonicalize(file).ok().and_then(|f| f.parent().map(Path::to_path_buf))
+    };
+    dir.and_then(|d| d.file_name().map(|n| n.to_string_lossy().to_string())).unwrap_or_default()
+}
+
+// What the run column holds: the key given with --group-by, the file with --partition-by file, temp0 with --ladder,
+// or the directory when several patterns are given. None for a single run.
+fn group_key(input: &InputArgs, output: &OutputArgs) -> Result<Option<GroupKey>, Box<dyn Error>> {
+    let by_file = matches!(output.partition_by, Some(Partition::File));
+    Ok(match &input.group_by {
+        Some(_) if by_file => return Err("--partition-by file groups the frames by file, it cannot be used with --group-by".into()),
+        Some(key) => Some(key.clone()),
+        None if by_file => Some(GroupKey::File),
+        None if output.ladder => Some(GroupKey::Setting("temp0".to_string())),
+        None => (input.patterns.len() > 1).then_some(GroupKey::Dir),
+    })
+}
+
+// The run of a file, for a key of --group-by. The files without the setting are put together in a "none" run.
+fn group_name(file: &Path, key: &GroupKey, settings: &BTreeMap<String, f64>, several_patterns: bool) -> String {
+    match key {
+        GroupKey::Dir => run_name(file),
+        GroupKey::File => file_run_name(file, several_patterns),
+        GroupKey::Setting(name) => settings.get(name).map_or("none".to_string(), |v| format!("{}={}", name, v)),
+    }
+}
+
+// Print the temperature and the heat capacity of each run of the ladder.
+fn print_ladder(ladder: &DataFrame) -> Result<(), Box<dyn Error>> {
+    println!("Temperature ladder:");
+    let column = |name: &str| -> Result<Vec<Option<f64>>, Box<dyn Error>> {
+        match ladder.column(name) {
+            Ok(column) => Ok(column.f64()?.into_iter().collect()),
+            Err(_) => Ok(vec![None; ladder.height()]),
+        }
+    };
+    let (temperatures, cv) = (column("TEMP(K)")?, column("Cv")?);
+    let runs = ladder.column(RUN_COLUMN)?;
+    for (i, run) in runs.utf8()?.into_iter().enumerate() {
+        let show = |v: Option<f64>| v.map_or("-".to_string(), |v| format!("{:.4}", v));
+        println!("    {}: TEMP(K) = {}, Cv = {} kcal/mol/K", run.unwrap_or_default(), show(temperatures[i]), show(cv[i]));
     }
     Ok(())
 }
 
-// Parses argument(s) from the command line, return the pattern used to select files to read and the path to the search directory.
-fn parse_args() -> Result<(String, String), Box<dyn Error>> {
-    let args: Vec<String> = std::env::args().collect();
-    if args.len() < 2 {
-        return Err("Not enough arguments provided. Usage: relis \"path/to/directory/pattern\" (glob style)".into());
+// The run of a file with --partition-by file: its name, after the name of its directory when several patterns are
+// given, as files of different directories often have the same name (run1/prod1.lis and run2/prod1.lis).
+fn file_run_name(file: &Path, several_patterns: bool) -> String {
+    let name = file.file_name().map_or_else(|| file.to_string_lossy(), |n| n.to_string_lossy()).to_string();
+    if several_patterns {
+        format!("{}/{}", run_name(file), name)
+    } else {
+        name
     }
-    if args[1] == "help" {
-        println!("Usage: relis \"path/to/directory/pattern\" (glob style)");
-        std::process::exit(0);
+}
+
+// Read a list of files, "-" for stdin: one path per line, empty lines and lines starting with '#' being left out,
+// or a JSON manifest written with --manifest, in which case the files from which values were extracted are read.
+fn read_file_list(list: &Path) -> Result<Vec<PathBuf>, RelisError> {
+    let text = if list == Path::new("-") {
+        std::io::read_to_string(std::io::stdin())?
+    } else {
+        std::fs::read_to_string(list).map_err(|e| RelisError::from(e).in_file(list))?
+    };
+    let invalid = |message: String| RelisError::Parse { path: Some(list.to_path_buf()), line: 0, message };
+    if text.trim_start().starts_with('{') {
+        let manifest: serde_json::Value = serde_json::from_str(&text).map_err(|e| RelisError::Parse {
+            path: Some(list.to_path_buf()),
+            line: e.line(),
+            message: e.to_string(),
+        })?;
+        let files = manifest["files"].as_array().ok_or_else(|| invalid("no \"files\" in the manifest".to_string()))?;
+        return files
+            .iter()
+            .map(|f| f["path"].as_str().map(PathBuf::from).ok_or_else(|| invalid("a file has no path".to_string())))
+            .collect();
     }
-    let path: PathBuf = PathBuf::from(args[1].clone());
+    Ok(text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(PathBuf::from)
+        .collect())
+}
+
+// Splits the pattern argument from the command line, return the pattern used to select files to read and the path to the search directory.
+// The path is split by the standard library, so that both separators are understood on Windows (runs\prod*.lis).
+fn parse_args(arg: &str) -> Result<(String, PathBuf), RelisError> {
+    let invalid = |message: &str| RelisError::Glob { pattern: arg.to_string(), message: message.to_string() };
+    let path: PathBuf = PathBuf::from(arg);
     let pattern: String = path
         .file_name()
-        .ok_or("Failed to extract file name from the provided path")?
-        .to_str()
-        .ok_or("File name is not valid UTF-8")?
-        .to_string();
-    let mut dir = std::env::current_dir()?
-        .to_str()
-        .ok_or("Current directory path is not valid UTF-8")?
-        .to_string();
-    if let Some(parent) = path.parent() {
-        dir = parent
+        .ok_or_else(|| invalid("failed to extract file name from the provided path"))?
         .to_str()
-        .ok_or("Parent directory path is not valid UTF-8")?
+        .ok_or_else(|| invalid("file name is not valid UTF-8"))?
         .to_string();
-    }
+    // A pattern without directory ("*.lis") has an empty parent.
+    let dir = match path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        Some(parent) => parent.to_path_buf(),
+        None => std::env::current_dir()?,
+    };
     Ok((pattern, dir))
 }
 
@@ -98,66 +1700,22 @@ fn parse_args() -> Result<(String, String), Box<dyn Error>> {
 // Returns a vector of strings containing the names of the files.
 // 1st arg: Path to the directory in which the files are to be searched.
 // 2nd arg: The pattern to be searched in the file names.
-fn list_files(path: &PathBuf, pattern: &str) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+fn list_files(path: &Path, pattern: &str) -> Result<Vec<PathBuf>, RelisError> {
     let mut files = Vec::new();
-    let pattern_str = format!("{}/{}", path.display(), pattern);
-    for entry in glob(&pattern_str)? {
+    let dir = path.to_str().ok_or_else(|| RelisError::Glob {
+        pattern: pattern.to_string(),
+        message: format!("directory path {} is not valid UTF-8", path.display()),
+    })?;
+    // The name of an existing directory is taken as it is, even with glob characters in it (run[1]), the others can
+    // be patterns (run*/prod*.lis).
+    let dir = if path.is_dir() { glob::Pattern::escape(dir) } else { dir.to_string() };
+    let pattern_str = Path::new(&dir).join(pattern).to_string_lossy().to_string();
+    let entries = glob(&pattern_str).map_err(|e| RelisError::Glob { pattern: pattern_str.clone(), message: e.to_string() })?;
+    for entry in entries {
         match entry {
             Ok(path) => files.push(path),
             Err(e) => println!("{:?}", e),
         }
     }
-    // Print the list of files found.
-    for file in &files {
-        println!("{}", file.display());
-    }
     Ok(files)
 }
-
-// Open a text file and read lines from it, keep only lines between two patterns.
-// Returns a vector of strings containing the lines in the file between the two patterns.
-// 1st arg: Path to the file to be read.
-// 2nd arg: The pattern that marks the beginning of the lines to be kept.
-// 3rd arg: The pattern that marks the end of the lines to be kept.
-fn read_lines_until_pattern(file_path: &PathBuf, pattern_start: &str, pattern_end: &str ) -> Result<Vec<String>, Box<dyn Error>> {
-    let file = std::fs::File::open(file_path)?;
-    let reader = std::io::BufReader::new(file);
-    let mut lines = Vec::new();
-    let mut start = false;
-    for line in reader.lines() {
-        let line = line?;
-        if line.contains(pattern_start) {
-            start = true;
-        }
-        if line.contains(pattern_end) {
-            break;
-        }
-        if start {
-            lines.push(line);
-        }
-    }
-    Ok(lines)
-}
-
-// A function that extract the list of the differents values available for each frame in the .lis file using regex.
-// The function returns a Result with a BTreeMap of <String, float> that contains the names of the name and values for each frame, or an error.
-// 1st arg: A vector of strings containing the lines that contain the values.
-fn extract_values(lines: &Vec<String>) -> Result<BTreeMap<String, Vec<f64>>, Box<dyn Error>> {
-    // Create a BTreeMap that will contain the types of values and their values for each frame.
-    let mut data = BTreeMap::new();
-    // Create a regex to capture the categories and the values.
-    let re = Regex::new(r"([1\-4\s]*[A-Za-z]+[\(A-Z)]*)\s+=\s+([-]?\d+[\.]?\d*)")?;
-    // For each line, search and add the value to the corresponding key in the data map.
-    for line in lines {
-        for cap in re.captures_iter(line) {
-            if line.contains("KE") || line.contains("err") {
-                continue;
-            }
-            let t = cap[1].trim().to_string();
-            // Convert the value to a float
-            let v = cap[2].parse::<f64>()?;
-            data.entry(t).or_insert(Vec::new()).push(v);
-        }
-    }
-    Ok(data)
-}
\ No newline at end of file
@@ -0,0 +1,95 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+
+// Description of a run of relis, written as JSON so that pipelines can check what was read and written.
+#[derive(Serialize)]
+pub struct Manifest {
+    pub version: &'static str,
+    // The command line used.
+    pub command: Vec<String>,
+    pub directory: PathBuf,
+    pub pattern: String,
+    // The files from which values were extracted.
+    pub files: Vec<ParsedFile>,
+    // The files matching the pattern from which nothing could be extracted.
+    pub skipped: Vec<SkippedFile>,
+    // The files that could not be read, with --keep-going.
+    pub failed: Vec<FailedFile>,
+    // The columns of the output table and its number of rows.
+    pub columns: Vec<String>,
+    pub frames: usize,
+    // Number of missing values of the columns that have some.
+    pub missing_values: BTreeMap<String, usize>,
+    // Where each column has values, with --coverage.
+    pub coverage: Vec<ColumnCoverage>,
+    pub warnings: Vec<String>,
+    // The segments not following the previous one, with --chain-check.
+    pub broken_chains: Vec<String>,
+    // The files written.
+    pub outputs: Vec<PathBuf>,
+}
+
+#[derive(Serialize)]
+pub struct ParsedFile {
+    pub path: PathBuf,
+    // The program that wrote the file, e.g. "amber" or "namd".
+    pub engine: String,
+    pub frames: usize,
+    pub columns: Vec<String>,
+    // The columns of the table that the file does not have, left empty in its rows.
+    pub missing_columns: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct ColumnCoverage {
+    pub column: String,
+    // Fraction of the frames of the table having a value.
+    pub fraction: f64,
+    // The first and last time (or number of the frame, from 1, without time column) at which it has a value.
+    pub first: Option<f64>,
+    pub last: Option<f64>,
+    // The files read that do not have the column.
+    pub absent_from: Vec<PathBuf>,
+}
+
+#[derive(Serialize)]
+pub struct SkippedFile {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+#[derive(Serialize)]
+pub struct FailedFile {
+    pub path: PathBuf,
+    pub error: String,
+}
+
+impl Manifest {
+    pub fn new(directory: &Path, pattern: &str) -> Manifest {
+        Manifest {
+            version: env!("CARGO_PKG_VERSION"),
+            command: std::env::args().collect(),
+            directory: directory.to_path_buf(),
+            pattern: pattern.to_string(),
+            files: Vec::new(),
+            skipped: Vec::new(),
+            failed: Vec::new(),
+            columns: Vec::new(),
+            frames: 0,
+            missing_values: BTreeMap::new(),
+            coverage: Vec::new(),
+            warnings: Vec::new(),
+            broken_chains: Vec::new(),
+            outputs: Vec::new(),
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let file = std::fs::File::create(path)?;
+        serde_json::to_writer_pretty(file, self)?;
+        println!("Manifest saved in {}", path.display());
+        Ok(())
+    }
+}
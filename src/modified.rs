@@ -0,0 +1,70 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use relis::RelisError;
+use crate::{archive, chain, remote};
+
+// Order in which the files are read, given with --order.
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum FileOrder {
+    // Natural order of the paths (prod2 before prod10).
+    Name,
+    // Oldest modified first.
+    Mtime,
+}
+
+// Parse a date given on the command line, "2024-06-01" or "2024-06-01T12:00:00", in UTC.
+pub fn parse_date(s: &str) -> Result<SystemTime, String> {
+    let invalid = || format!("expected a date such as 2024-06-01 or 2024-06-01T12:00:00, got \"{}\"", s);
+    let (date, time) = s.trim().split_once(['T', ' ']).unwrap_or((s.trim(), "00:00:00"));
+    let numbers = |text: &str, count: usize| -> Option<Vec<i64>> {
+        let numbers: Vec<i64> = text.split([':', '-']).map(|n| n.parse().ok()).collect::<Option<_>>()?;
+        (numbers.len() == count).then_some(numbers)
+    };
+    let (date, time) = (numbers(date, 3).ok_or_else(invalid)?, numbers(time.trim_end_matches('Z'), 3).ok_or_else(invalid)?);
+    let (year, month, day) = (date[0], date[1], date[2]);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || time[0] > 23 || time[1] > 59 || time[2] > 60 {
+        return Err(invalid());
+    }
+    // Number of days since 1970-01-01 (proleptic Gregorian calendar), the inverse of the date of the timestamps.
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146097 + doe - 719468;
+    let seconds = days * 86400 + time[0] * 3600 + time[1] * 60 + time[2];
+    let seconds = u64::try_from(seconds).map_err(|_| format!("the date \"{}\" is before 1970", s))?;
+    Ok(UNIX_EPOCH + Duration::from_secs(seconds))
+}
+
+// The time of the last modification of a file. The members of an archive have the time of the archive,
+// the remote files have none.
+pub fn modified(file: &Path) -> Result<SystemTime, RelisError> {
+    let name = file.to_string_lossy();
+    if remote::is_remote(&name) {
+        return Err(RelisError::io(file, std::io::ErrorKind::Unsupported, "remote files have no modification time"));
+    }
+    let path = if archive::is_archive(&name) { archive::split(&name)?.0 } else { file.to_path_buf() };
+    std::fs::metadata(&path).and_then(|m| m.modified()).map_err(|e| RelisError::from(e).in_file(&path))
+}
+
+// Keep the files modified after a time, if given, and sort them in the given order.
+pub fn select(files: Vec<PathBuf>, order: Option<FileOrder>, newer_than: Option<SystemTime>) -> Result<Vec<PathBuf>, RelisError> {
+    let needs_time = newer_than.is_some() || matches!(order, Some(FileOrder::Mtime));
+    let mut files = files
+        .into_iter()
+        .map(|f| {
+            let time = if needs_time { Some(modified(&f)?) } else { None };
+            Ok((f, time))
+        })
+        .collect::<Result<Vec<_>, RelisError>>()?;
+    if let Some(threshold) = newer_than {
+        files.retain(|(_, time)| time.is_some_and(|t| t > threshold));
+    }
+    match order {
+        Some(FileOrder::Name) => files.sort_by(|(a, _), (b, _)| chain::natural_cmp(&a.to_string_lossy(), &b.to_string_lossy())),
+        Some(FileOrder::Mtime) => files.sort_by_key(|(_, time)| *time),
+        None => {}
+    }
+    Ok(files.into_iter().map(|(f, _)| f).collect())
+}
@@ -0,0 +1,101 @@
+// Parsing of NAMD logs: the values are in the "ENERGY:" lines, named by the last "ETITLE:" line.
+// The fields are renamed to their AMBER names when there is one, so that NAMD and AMBER tables can be combined.
+use std::collections::BTreeMap;
+use crate::parser::Parser;
+use crate::RelisError;
+
+// AMBER name of the NAMD fields that have one.
+const AMBER_NAMES: [(&str, &str); 11] = [
+    ("TS", "NSTEP"),
+    ("BOND", "BOND"),
+    ("ANGLE", "ANGLE"),
+    ("DIHED", "DIHED"),
+    ("ELECT", "EELEC"),
+    ("VDW", "VDWAALS"),
+    ("KINETIC", "EKtot"),
+    ("TOTAL", "Etot"),
+    ("TEMP", "TEMP(K)"),
+    ("POTENTIAL", "EPtot"),
+    ("PRESSURE", "PRESS"),
+];
+
+// What is needed to read the next ENERGY: lines of a log.
+#[derive(Default)]
+pub struct Reader {
+    // Names of the columns of the ENERGY: lines.
+    titles: Vec<String>,
+    // Time step in fs, from the "Info: TIMESTEP" line.
+    timestep: Option<f64>,
+}
+
+impl Reader {
+    // Read a line of the log, the values of an ENERGY: line are added to data.
+    pub fn line(&mut self, line: &str, data: &mut BTreeMap<String, Vec<f64>>) -> Result<(), RelisError> {
+        if let Some(titles) = line.strip_prefix("ETITLE:") {
+            self.titles = titles.split_whitespace().map(amber_name).collect();
+            return Ok(());
+        }
+        if let Some(timestep) = line.strip_prefix("Info: TIMESTEP") {
+            self.timestep = timestep.trim().parse().ok();
+            return Ok(());
+        }
+        let Some(values) = line.strip_prefix("ENERGY:") else {
+            return Ok(());
+        };
+        if self.titles.is_empty() {
+            return Err(RelisError::parse("ENERGY: line found before any ETITLE: line"));
+        }
+        for (title, value) in self.titles.iter().zip(values.split_whitespace()) {
+            let value = value.parse::<f64>()?;
+            if title == "NSTEP" {
+                if let Some(timestep) = self.timestep {
+                    data.entry("TIME(PS)".to_string()).or_default().push(value * timestep / 1000.0);
+                }
+            }
+            data.entry(title.clone()).or_default().push(value);
+        }
+        Ok(())
+    }
+}
+
+impl Parser for Reader {
+    fn read_line(&mut self, line: &str, data: &mut BTreeMap<String, Vec<f64>>) -> Result<bool, RelisError> {
+        self.line(line, data)?;
+        Ok(true)
+    }
+}
+
+// NAMD logs print their name in the first "Info:" lines, or start with the titles of the energies.
+pub fn detect(head: &str) -> bool {
+    head.contains("Info: NAMD") || head.contains("ETITLE:")
+}
+
+fn amber_name(title: &str) -> String {
+    AMBER_NAMES
+        .iter()
+        .find(|(namd, _)| *namd == title)
+        .map_or(title, |(_, amber)| amber)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn energy_lines_are_read_with_the_amber_names() {
+        let log = "Info: NAMD 2.14 for Linux-x86_64-multicore\nInfo: TIMESTEP               2\n\
+                   ETITLE:      TS           BOND          ANGLE          ELECT            VDW           TEMP\n\
+                   ENERGY:       0        10.5000        20.2500      -300.0000       -40.0000       300.0000\n\
+                   ENERGY:     500        11.5000        21.2500      -301.0000       -41.0000       301.0000\n";
+        assert!(detect(log));
+        let mut reader = Reader::default();
+        let mut data = BTreeMap::new();
+        for line in log.lines() {
+            reader.line(line, &mut data).unwrap();
+        }
+        assert_eq!(data.keys().collect::<Vec<_>>(), vec!["ANGLE", "BOND", "EELEC", "NSTEP", "TEMP(K)", "TIME(PS)", "VDWAALS"]);
+        assert_eq!(data["TIME(PS)"], vec![0.0, 1.0]);
+        assert_eq!(data["EELEC"], vec![-300.0, -301.0]);
+    }
+}
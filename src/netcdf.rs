@@ -0,0 +1,136 @@
+use std::error::Error;
+use std::path::Path;
+use polars::prelude::*;
+
+// Writer of NetCDF files in the classic format with 64-bit offsets (CDF-2), which every NetCDF library reads,
+// see https://docs.unidata.ucar.edu/netcdf-c/current/file_format_specifications.html.
+// Each column is a double variable over the unlimited dimension, the time column being its coordinate variable.
+
+const NC_DIMENSION: i32 = 0x0A;
+const NC_VARIABLE: i32 = 0x0B;
+const NC_ATTRIBUTE: i32 = 0x0C;
+const NC_CHAR: i32 = 2;
+const NC_DOUBLE: i32 = 6;
+
+// A variable to write, with its attributes.
+struct Variable {
+    name: String,
+    long_name: String,
+    units: Option<&'static str>,
+    description: Option<&'static str>,
+    values: Vec<f64>,
+}
+
+// Write the table to a NetCDF file.
+// 1st arg: Path of the file to create.
+// 2nd arg: The table.
+// 3rd arg: The name of the time column, if any. Without it the dimension is "frame".
+// 4th arg: The unit of each column of the table, if known.
+// 5th arg: The meaning of each column of the table, if known.
+pub fn write(path: &Path, df: &DataFrame, time: Option<&str>, units: &[Option<&'static str>],
+             descriptions: &[Option<&'static str>]) -> Result<(), Box<dyn Error>> {
+    let dimension = if time.is_some() { "time" } else { "frame" };
+    let mut variables = Vec::new();
+    // Text columns (the run of each frame) are left out.
+    let columns = df.get_columns().iter().zip(units.iter().zip(descriptions));
+    for (column, (units, description)) in columns.filter(|(c, _)| c.dtype().is_numeric()) {
+        let values = column.cast(&DataType::Float64)?;
+        let values: Vec<f64> = values.f64()?.into_iter().map(|v| v.unwrap_or(f64::NAN)).collect();
+        let name = if Some(column.name()) == time { dimension.to_string() } else { variable_name(column.name()) };
+        let long_name = column.name().to_string();
+        variables.push(Variable { name, long_name, units: *units, description: *description, values });
+    }
+    // The coordinate variable comes first, as in the files of AMBER.
+    variables.sort_by_key(|v| v.name != dimension);
+
+    let mut header = Vec::new();
+    header.extend_from_slice(b"CDF\x02");
+    put_int(&mut header, df.height() as i32);
+    // A single dimension, the unlimited one (its length is given by the number of records).
+    put_int(&mut header, NC_DIMENSION);
+    put_int(&mut header, 1);
+    put_name(&mut header, dimension);
+    put_int(&mut header, 0);
+    // Global attributes.
+    let history = std::env::args().collect::<Vec<String>>().join(" ");
+    let global = [("program", "relis"), ("programVersion", env!("CARGO_PKG_VERSION")), ("history", history.as_str())];
+    put_int(&mut header, NC_ATTRIBUTE);
+    put_int(&mut header, global.len() as i32);
+    for (name, value) in global {
+        put_text_attribute(&mut header, name, value);
+    }
+    put_int(&mut header, NC_VARIABLE);
+    put_int(&mut header, variables.len() as i32);
+    // The offsets of the data are only known once the size of the header is, they are patched afterwards.
+    let mut begin_positions = Vec::new();
+    for variable in &variables {
+        put_name(&mut header, &variable.name);
+        // One dimension, the first (and only) one.
+        put_int(&mut header, 1);
+        put_int(&mut header, 0);
+        let attributes = 2 + variable.units.is_some() as i32 + variable.description.is_some() as i32;
+        put_int(&mut header, NC_ATTRIBUTE);
+        put_int(&mut header, attributes);
+        put_text_attribute(&mut header, "long_name", &variable.long_name);
+        if let Some(units) = variable.units {
+            put_text_attribute(&mut header, "units", units);
+        }
+        if let Some(description) = variable.description {
+            put_text_attribute(&mut header, "description", description);
+        }
+        put_name(&mut header, "_FillValue");
+        put_int(&mut header, NC_DOUBLE);
+        put_int(&mut header, 1);
+        header.extend_from_slice(&f64::NAN.to_be_bytes());
+        put_int(&mut header, NC_DOUBLE);
+        // Size of a record of the variable.
+        put_int(&mut header, 8);
+        begin_positions.push(header.len());
+        header.extend_from_slice(&0i64.to_be_bytes());
+    }
+    // The records follow the header, each record holding one value of each variable.
+    for (i, position) in begin_positions.iter().enumerate() {
+        let begin = (header.len() + 8 * i) as i64;
+        header[*position..*position + 8].copy_from_slice(&begin.to_be_bytes());
+    }
+    let mut data = header;
+    for row in 0..df.height() {
+        for variable in &variables {
+            data.extend_from_slice(&variable.values[row].to_be_bytes());
+        }
+    }
+    std::fs::write(path, data)?;
+    Ok(())
+}
+
+// A NetCDF name can't contain '/', and is easier to use in xarray without spaces and parentheses,
+// e.g. "TEMP(K)" -> "TEMP_K", "1-4 EEL" -> "1-4_EEL". The original name is kept in the long_name attribute.
+fn variable_name(column: &str) -> String {
+    let name: String = column
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+        .collect();
+    name.trim_end_matches('_').to_string()
+}
+
+fn put_int(buf: &mut Vec<u8>, value: i32) {
+    buf.extend_from_slice(&value.to_be_bytes());
+}
+
+// Write bytes followed by the zeros padding them to a multiple of 4.
+fn put_padded(buf: &mut Vec<u8>, bytes: &[u8]) {
+    buf.extend_from_slice(bytes);
+    buf.resize(buf.len() + (4 - bytes.len() % 4) % 4, 0);
+}
+
+fn put_name(buf: &mut Vec<u8>, name: &str) {
+    put_int(buf, name.len() as i32);
+    put_padded(buf, name.as_bytes());
+}
+
+fn put_text_attribute(buf: &mut Vec<u8>, name: &str, value: &str) {
+    put_name(buf, name);
+    put_int(buf, NC_CHAR);
+    put_int(buf, value.len() as i32);
+    put_padded(buf, value.as_bytes());
+}
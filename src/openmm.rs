@@ -0,0 +1,98 @@
+// Parsing of the CSV files written by the StateDataReporter of OpenMM: a header line starting with '#'
+// and holding the quoted names of the columns, then one line of values per report.
+// The columns having an AMBER equivalent are renamed and converted to AMBER units, the others keep their header.
+use std::collections::BTreeMap;
+use crate::parser::Parser;
+use crate::RelisError;
+
+const KJ_PER_KCAL: f64 = 4.184;
+
+// AMBER name of the OpenMM columns that have one, with the factor converting the value to AMBER units.
+const AMBER_NAMES: [(&str, &str, f64); 8] = [
+    ("Step", "NSTEP", 1.0),
+    ("Time (ps)", "TIME(PS)", 1.0),
+    ("Potential Energy (kJ/mole)", "EPtot", 1.0 / KJ_PER_KCAL),
+    ("Kinetic Energy (kJ/mole)", "EKtot", 1.0 / KJ_PER_KCAL),
+    ("Total Energy (kJ/mole)", "Etot", 1.0 / KJ_PER_KCAL),
+    ("Temperature (K)", "TEMP(K)", 1.0),
+    // nm^3 to A^3.
+    ("Box Volume (nm^3)", "VOLUME", 1000.0),
+    ("Density (g/mL)", "Density", 1.0),
+];
+
+#[derive(Default)]
+pub struct Reader {
+    // AMBER (or OpenMM) names of the columns, with their conversion factor.
+    columns: Vec<(String, f64)>,
+    // The separator given to the reporter, "," by default.
+    separator: String,
+}
+
+impl Reader {
+    // Read a line of the file, the values of the reports are added to data.
+    pub fn line(&mut self, line: &str, data: &mut BTreeMap<String, Vec<f64>>) -> Result<(), RelisError> {
+        if let Some(header) = line.strip_prefix('#') {
+            // The separator follows the first quoted name: #"Step","Time (ps)",...
+            self.separator = header
+                .strip_prefix('"')
+                .and_then(|h| h.split_once('"'))
+                .and_then(|(_, rest)| rest.chars().next())
+                .map_or(",".to_string(), |c| c.to_string());
+            self.columns = header.split(self.separator.as_str()).map(|h| amber_name(h.trim().trim_matches('"'))).collect();
+            return Ok(());
+        }
+        if self.columns.is_empty() || line.trim().is_empty() {
+            return Ok(());
+        }
+        // Values that are not numbers (e.g. "--" for the first speed) are NaN.
+        for ((name, factor), value) in self.columns.iter().zip(line.split(self.separator.as_str())) {
+            // The remaining time is written as a duration ("1:23:45").
+            if name == "Time Remaining" {
+                continue;
+            }
+            let value = value.trim().parse::<f64>().map_or(f64::NAN, |v| v * factor);
+            data.entry(name.clone()).or_default().push(value);
+        }
+        Ok(())
+    }
+}
+
+impl Parser for Reader {
+    fn read_line(&mut self, line: &str, data: &mut BTreeMap<String, Vec<f64>>) -> Result<bool, RelisError> {
+        self.line(line, data)?;
+        Ok(true)
+    }
+}
+
+// The header of the StateDataReporter files starts with the first quoted name.
+pub fn detect(head: &str) -> bool {
+    head.starts_with("#\"")
+}
+
+fn amber_name(header: &str) -> (String, f64) {
+    AMBER_NAMES
+        .iter()
+        .find(|(openmm, _, _)| *openmm == header)
+        .map_or((header.to_string(), 1.0), |(_, amber, factor)| (amber.to_string(), *factor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_are_read_in_amber_units() {
+        let csv = "#\"Step\",\"Time (ps)\",\"Potential Energy (kJ/mole)\",\"Box Volume (nm^3)\",\"Speed (ns/day)\"\n\
+                   1000,2.0,-418.4,27.0,--\n2000,4.0,-836.8,27.5,95.3\n";
+        assert!(detect(csv));
+        let mut reader = Reader::default();
+        let mut data = BTreeMap::new();
+        for line in csv.lines() {
+            reader.line(line, &mut data).unwrap();
+        }
+        assert_eq!(data.keys().collect::<Vec<_>>(), vec!["EPtot", "NSTEP", "Speed (ns/day)", "TIME(PS)", "VOLUME"]);
+        assert!(data["EPtot"].iter().zip([-100.0, -200.0]).all(|(v, expected)| (v - expected).abs() < 1e-9));
+        assert_eq!(data["VOLUME"], vec![27000.0, 27500.0]);
+        assert!(data["Speed (ns/day)"][0].is_nan());
+    }
+}
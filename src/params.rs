@@ -0,0 +1,95 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::path::PathBuf;
+use clap::Args;
+use polars::prelude::*;
+use regex::Regex;
+use relis::{Engine, Lines};
+use crate::InputArgs;
+
+#[derive(Args)]
+pub struct ParamsArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    /// Also write the table of the parameters, one row per file, in this CSV file.
+    #[arg(long, value_name = "FILE")]
+    csv: Option<PathBuf>,
+    /// Also print the parameters that are the same in all the files.
+    #[arg(long)]
+    all: bool,
+}
+
+// Print the parameters echoed at the beginning of the AMBER files (the &cntrl and &ewald namelists and the control
+// data of the run) that differ between the files, e.g. a segment run with the wrong cutoff or thermostat.
+pub fn params(args: &ParamsArgs) -> Result<(), Box<dyn Error>> {
+    let (_, _, files) = crate::find_files(&args.input)?;
+    let setting_re = Regex::new(crate::SETTING_REGEX)?;
+    let mut rows: Vec<(String, BTreeMap<String, f64>)> = Vec::new();
+    for file in &files {
+        let crate::OpenedFile { engine, reader } = args.input.open(file)?;
+        if !matches!(engine.unwrap_or_default(), Engine::Amber | Engine::AmberMin) {
+            println!("{}: not an AMBER file, left out", file.display());
+            continue;
+        }
+        // The parameters are echoed before the results.
+        let mut settings = BTreeMap::new();
+        for line in Lines::new(reader) {
+            let line = line.map_err(|e| relis::RelisError::from(e).in_file(file))?;
+            if args.input.start_pattern.is_match(&line) {
+                break;
+            }
+            crate::read_settings(&line, &setting_re, &mut settings);
+        }
+        rows.push((file.display().to_string(), settings));
+    }
+    let names: BTreeSet<&String> = rows.iter().flat_map(|(_, settings)| settings.keys()).collect();
+    let mut same = Vec::new();
+    let mut differing = Vec::new();
+    for name in &names {
+        // The files having each value of the parameter, "none" for the files without it.
+        let mut values: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+        for (file, settings) in &rows {
+            let value = settings.get(*name).map_or("none".to_string(), f64::to_string);
+            values.entry(value).or_default().push(file);
+        }
+        if values.len() == 1 {
+            same.push(format!("{} = {}", name, values.keys().next().unwrap_or(&String::new())));
+            continue;
+        }
+        // The most common value is given with its number of files, the others with their files.
+        let common = values.iter().max_by_key(|(_, files)| files.len()).map(|(value, _)| value.clone()).unwrap_or_default();
+        let described: Vec<String> = values
+            .iter()
+            .map(|(value, files)| {
+                if *value == common {
+                    format!("{} in {} files", value, files.len())
+                } else {
+                    format!("{} in {}", value, files.join(", "))
+                }
+            })
+            .collect();
+        differing.push(format!("{}: {}", name, described.join("; ")));
+    }
+    println!("{} parameters read from {} files", names.len(), rows.len());
+    if differing.is_empty() {
+        println!("All the parameters are the same in all the files");
+    } else {
+        println!("Parameters differing between the files:");
+        for line in &differing {
+            println!("    {}", line);
+        }
+    }
+    if args.all && !same.is_empty() {
+        println!("Same in all the files: {}", same.join(", "));
+    }
+    if let Some(path) = &args.csv {
+        let mut columns = vec![Series::new("file", rows.iter().map(|(file, _)| file.as_str()).collect::<Vec<_>>())];
+        for name in &names {
+            columns.push(Series::new(name, rows.iter().map(|(_, settings)| settings.get(*name).copied()).collect::<Vec<_>>()));
+        }
+        let mut df = DataFrame::new(columns)?;
+        CsvWriter::new(&mut std::fs::File::create(path)?).finish(&mut df)?;
+        println!("Parameters saved in {}", path.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,135 @@
+// Registry of the formats that can be read. Each format is a module with a Parser reading its files one line at a
+// time and a function recognizing them from their first bytes, listed in FORMATS: adding a format (a new version of
+// AMBER, the wrapper of a site) is adding its module and its entry, the Extractor is left as it is.
+use std::collections::BTreeMap;
+use regex::Regex;
+use crate::{amber_min, gamd, gromacs, lammps, namd, openmm, read_fields, Engine, Frames, RelisError, Section};
+
+// Reading of the files of a format.
+pub trait Parser: Send {
+    // Read a line of the file, its values are added to data.
+    // Returns true if the line is in the part of the file that is read.
+    fn read_line(&mut self, line: &str, data: &mut BTreeMap<String, Vec<f64>>) -> Result<bool, RelisError>;
+
+    // Returns true when the rest of the file has nothing to be read.
+    fn ended(&self) -> bool {
+        false
+    }
+}
+
+// The settings given on the command line that the parsers may use: the patterns and the regex of the AMBER files.
+pub struct Options<'a> {
+    pub pattern_start: &'a Regex,
+    pub pattern_end: &'a Regex,
+    pub re: &'a Regex,
+}
+
+// A format that can be read, selected with --engine or detected.
+pub struct Format {
+    pub engine: Engine,
+    // Name given with --engine.
+    pub name: &'static str,
+    // Returns true if the first bytes of a file (DETECTION_BYTES) are in this format.
+    pub detect: fn(&str) -> bool,
+    pub parser: fn(&Options) -> Box<dyn Parser>,
+}
+
+// The formats, in the order in which they are tried to detect the format of a file.
+pub static FORMATS: &[Format] = &[
+    Format { engine: Engine::Openmm, name: "openmm", detect: openmm::detect, parser: |_| Box::<openmm::Reader>::default() },
+    Format { engine: Engine::Lammps, name: "lammps", detect: lammps::detect, parser: |_| Box::<lammps::Reader>::default() },
+    Format { engine: Engine::Gromacs, name: "gromacs", detect: gromacs::detect, parser: |_| Box::<gromacs::Reader>::default() },
+    Format { engine: Engine::Namd, name: "namd", detect: namd::detect, parser: |_| Box::<namd::Reader>::default() },
+    Format { engine: Engine::Gamd, name: "gamd", detect: gamd::detect, parser: |_| Box::<gamd::Reader>::default() },
+    Format { engine: Engine::AmberMin, name: "amber-min", detect: amber_min::detect, parser: |o| Box::new(Amber::new(o, true)) },
+    Format { engine: Engine::Amber, name: "amber", detect: detect_amber, parser: |o| Box::new(Amber::new(o, false)) },
+];
+
+// The format of an engine.
+pub fn format(engine: Engine) -> &'static Format {
+    FORMATS.iter().find(|f| f.engine == engine).expect("every engine is in FORMATS")
+}
+
+// sander and pmemd outputs name the program in their header.
+pub fn detect_amber(head: &str) -> bool {
+    head.contains("SANDER") || head.contains("PMEMD") || head.contains("Amber")
+}
+
+// Parsing of the AMBER files: the values of the lines between the two patterns, matched by the regex.
+pub struct Amber {
+    section: Section,
+    frames: Frames,
+    pattern_start: Regex,
+    pattern_end: Regex,
+    re: Regex,
+    // The regex is the default one.
+    default: bool,
+    minimization: Option<amber_min::Reader>,
+}
+
+impl Amber {
+    pub fn new(options: &Options, minimization: bool) -> Amber {
+        Amber {
+            section: Section::default(),
+            frames: Frames::default(),
+            pattern_start: options.pattern_start.clone(),
+            pattern_end: options.pattern_end.clone(),
+            re: options.re.clone(),
+            default: crate::is_default_regex(options.re),
+            minimization: minimization.then(amber_min::Reader::default),
+        }
+    }
+}
+
+impl Parser for Amber {
+    fn read_line(&mut self, line: &str, data: &mut BTreeMap<String, Vec<f64>>) -> Result<bool, RelisError> {
+        if !self.section.keep(line, &self.pattern_start, &self.pattern_end) {
+            return Ok(false);
+        }
+        let mut line_data = BTreeMap::new();
+        if let Some(reader) = &mut self.minimization {
+            reader.line(line, &self.re, self.default, &mut line_data)?;
+        } else {
+            read_fields(line, &self.re, self.default, &mut line_data)?;
+        }
+        self.frames.add(line, line_data, data);
+        Ok(true)
+    }
+
+    fn ended(&self) -> bool {
+        self.section.ended
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The beginning of a file of each format, detected in the order of FORMATS.
+    const HEADS: [(&str, Engine); 7] = [
+        ("#\"Step\",\"Time (ps)\",\"Potential Energy (kJ/mole)\"\n1000,2.0,-1234.5\n", Engine::Openmm),
+        ("LAMMPS (2 Aug 2023)\nunits real\n", Engine::Lammps),
+        ("                      :-) GROMACS - gmx mdrun, 2023.1 (-:\n", Engine::Gromacs),
+        ("Info: NAMD 2.14 for Linux-x86_64-multicore\nETITLE:      TS           BOND\n", Engine::Namd),
+        // Written by pmemd, but recognized from the boost energies before being taken for an AMBER file.
+        ("# Gaussian accelerated Molecular Dynamics log file (PMEMD)\n# ntwx,total_nstep,Boost-Energy-Potential\n",
+            Engine::Gamd),
+        ("          Amber 22 PMEMD 2022\n &cntrl\n  imin = 1, maxcyc = 500,\n", Engine::AmberMin),
+        ("          Amber 22 SANDER 2022\n &cntrl\n  imin = 0, nstlim = 500,\n", Engine::Amber),
+    ];
+
+    #[test]
+    fn formats_are_detected_in_order() {
+        for (head, engine) in HEADS {
+            assert_eq!(Engine::detect(head), Some(engine), "{}", head);
+        }
+        assert_eq!(Engine::detect("some text\n"), None);
+    }
+
+    #[test]
+    fn every_engine_has_a_format() {
+        for engine in Engine::ALL {
+            assert_eq!(format(engine).engine, engine);
+        }
+    }
+}
@@ -0,0 +1,31 @@
+// Energies of the QM region printed by sander QM/MM runs: ESCF among the MM terms of each frame, and with
+// verbosity > 0 the "QMMM:" lines (e.g. "QMMM: SCF Energy = -23.1 KCal/mol"). They are named with a "QMMM_"
+// prefix (QMMM_ESCF, QMMM_SCF_Energy), so that they are told apart from the MM terms.
+use std::collections::BTreeMap;
+use crate::RelisError;
+
+pub const PREFIX: &str = "QMMM_";
+
+// The fields of the MM lines that belong to the QM region.
+const QM_FIELDS: [&str; 2] = ["ESCF", "DFTBESCF"];
+
+// The name of a field read by the default regex, with the prefix if it belongs to the QM region.
+pub fn field_name(key: &str) -> String {
+    if QM_FIELDS.contains(&key) {
+        format!("{}{}", PREFIX, key)
+    } else {
+        key.to_string()
+    }
+}
+
+// Read a "QMMM:" line, its values are added to data. Returns false if the line is not a QMMM: line.
+pub fn line(line: &str, data: &mut BTreeMap<String, Vec<f64>>) -> Result<bool, RelisError> {
+    let Some(rest) = line.trim_start().strip_prefix("QMMM:") else {
+        return Ok(false);
+    };
+    // The energy may follow in another unit, without a name: only the value after "=" is read.
+    for (name, value) in crate::named_values(rest)? {
+        data.entry(format!("{}{}", PREFIX, name)).or_default().push(value);
+    }
+    Ok(true)
+}
@@ -0,0 +1,50 @@
+use std::io::{Read, Result};
+use std::sync::mpsc::{sync_channel, Receiver};
+
+// A file read in a thread, ahead of the parsing: the next chunks are read while the previous ones are parsed, which
+// hides the latency of network file systems (Lustre, NFS). The thread stops when the reader is dropped.
+pub struct ReadAhead {
+    receiver: Receiver<Result<Vec<u8>>>,
+    chunk: Vec<u8>,
+    // Position of the next byte to give in the chunk.
+    position: usize,
+}
+
+// Number of chunks read in advance.
+const CHUNKS_AHEAD: usize = 2;
+
+impl ReadAhead {
+    pub fn new(mut reader: impl Read + Send + 'static, chunk_size: usize) -> ReadAhead {
+        let (sender, receiver) = sync_channel(CHUNKS_AHEAD);
+        std::thread::spawn(move || loop {
+            let mut chunk = vec![0; chunk_size];
+            let read = reader.read(&mut chunk);
+            let end = matches!(read, Ok(0) | Err(_));
+            let sent = sender.send(read.map(|n| {
+                chunk.truncate(n);
+                chunk
+            }));
+            if end || sent.is_err() {
+                break;
+            }
+        });
+        ReadAhead { receiver, chunk: Vec::new(), position: 0 }
+    }
+}
+
+impl Read for ReadAhead {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if self.position == self.chunk.len() {
+            // The end of the file is an empty chunk, or the thread having stopped.
+            self.chunk = match self.receiver.recv() {
+                Ok(chunk) => chunk?,
+                Err(_) => Vec::new(),
+            };
+            self.position = 0;
+        }
+        let n = buf.len().min(self.chunk.len() - self.position);
+        buf[..n].copy_from_slice(&self.chunk[self.position..self.position + n]);
+        self.position += n;
+        Ok(n)
+    }
+}
@@ -0,0 +1,157 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdout, Command, Stdio};
+use relis::RelisError;
+
+// Files on other machines, given as ssh://host/path or s3://bucket/key. They are listed and read through
+// the ssh and aws command line tools, which take care of the authentication, and streamed without copies on disk.
+
+// Return true if the path or pattern is a remote one.
+pub fn is_remote(path: &str) -> bool {
+    path.starts_with("ssh://") || path.starts_with("s3://")
+}
+
+// List the remote files matching a pattern, e.g. "ssh://host/path/prod*.lis" or "s3://bucket/prefix/prod*.lis".
+// A pattern ending with '/' matches all the files of the directory (or S3 prefix).
+pub fn list(pattern: &str) -> Result<Vec<PathBuf>, RelisError> {
+    if let Some(rest) = pattern.strip_prefix("ssh://") {
+        let (host, path) = split_url(pattern, rest)?;
+        let (dir, name) = path.rsplit_once('/').unwrap_or(("", path));
+        let name = if name.is_empty() { "*" } else { name };
+        // The file name is left unquoted for the remote shell to expand it, so it must not hold other special characters.
+        if !name.chars().all(|c| c.is_ascii_alphanumeric() || "*?[]._-+".contains(c)) {
+            return Err(invalid_pattern(pattern, format!("unsupported characters in \"{}\"", name)));
+        }
+        // No match is not an error, as for local patterns.
+        let script = format!("cd {} && {{ ls -1d -- {} 2>/dev/null || true; }}", quote(&format!("/{}", dir)), name);
+        let output = run(pattern, Command::new("ssh").arg("--").arg(host).arg(script))?;
+        let dir = if dir.is_empty() { String::new() } else { format!("{}/", dir) };
+        return Ok(output.lines().map(|f| PathBuf::from(format!("ssh://{}/{}{}", host, dir, f))).collect());
+    }
+    if let Some(rest) = pattern.strip_prefix("s3://") {
+        let (bucket, key) = split_url(pattern, rest)?;
+        let (prefix, name) = key.rsplit_once('/').map_or(("", key), |(p, n)| (p, n));
+        let name = glob::Pattern::new(if name.is_empty() { "*" } else { name })
+            .map_err(|e| invalid_pattern(pattern, e.to_string()))?;
+        let dir = if prefix.is_empty() { String::new() } else { format!("{}/", prefix) };
+        let output = run(pattern, Command::new("aws").args(["s3", "ls", &format!("s3://{}/{}", bucket, dir)]))?;
+        // Lines are "date time size name", or "PRE name/" for the sub-prefixes.
+        let files = output
+            .lines()
+            .filter(|l| !l.trim_start().starts_with("PRE "))
+            .filter_map(s3_name)
+            .filter(|f| name.matches(f))
+            .map(|f| PathBuf::from(format!("s3://{}/{}{}", bucket, dir, f)))
+            .collect();
+        return Ok(files);
+    }
+    Err(invalid_pattern(pattern, "not a remote path"))
+}
+
+// Open a remote file, its content is read as it is received.
+pub fn open(path: &Path) -> Result<RemoteFile, RelisError> {
+    let url = path
+        .to_str()
+        .ok_or_else(|| RelisError::io(path, std::io::ErrorKind::InvalidInput, "remote path is not valid UTF-8"))?;
+    let mut command = if let Some(rest) = url.strip_prefix("ssh://") {
+        let (host, path) = split_url(url, rest)?;
+        let mut command = Command::new("ssh");
+        command.arg("--").arg(host).arg(format!("cat -- {}", quote(&format!("/{}", path))));
+        command
+    } else if url.starts_with("s3://") {
+        let mut command = Command::new("aws");
+        command.args(["s3", "cp", "--quiet", url, "-"]);
+        command
+    } else {
+        return Err(invalid_pattern(url, "not a remote path"));
+    };
+    let mut child = command.stdout(Stdio::piped()).spawn().map_err(|e| spawn_error(url, &command, e))?;
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| RelisError::io(path, std::io::ErrorKind::BrokenPipe, "failed to read the output of the remote command"))?;
+    Ok(RemoteFile { child, stdout })
+}
+
+// The output of the command reading a remote file. Reaching its end fails if the command failed.
+pub struct RemoteFile {
+    child: Child,
+    stdout: ChildStdout,
+}
+
+impl Read for RemoteFile {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.stdout.read(buf)?;
+        if n == 0 && !buf.is_empty() {
+            let status = self.child.wait()?;
+            if !status.success() {
+                return Err(std::io::Error::other(format!("the remote command failed ({})", status)));
+            }
+        }
+        Ok(n)
+    }
+}
+
+// The name of the object in a line of "aws s3 ls" (names may contain spaces).
+fn s3_name(line: &str) -> Option<&str> {
+    let mut rest = line.trim_start();
+    for _ in 0..3 {
+        rest = rest[rest.find(char::is_whitespace)?..].trim_start();
+    }
+    Some(rest.trim_end()).filter(|name| !name.is_empty())
+}
+
+// The command is stopped if the file is not read to its end.
+impl Drop for RemoteFile {
+    fn drop(&mut self) {
+        if let Ok(None) = self.child.try_wait() {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
+
+// "host/path/to/file" -> ("host", "path/to/file").
+// A host starting with '-' would be taken as an option by ssh (e.g. -oProxyCommand=...), running a local command.
+fn split_url<'a>(url: &str, rest: &'a str) -> Result<(&'a str, &'a str), RelisError> {
+    match rest.split_once('/') {
+        Some((host, _)) if host.starts_with('-') => Err(invalid_pattern(url, "the host (or bucket) cannot start with '-'")),
+        Some((host, path)) if !host.is_empty() => Ok((host, path)),
+        _ => Err(invalid_pattern(url, "expected a host (or bucket) and a path")),
+    }
+}
+
+fn invalid_pattern(pattern: &str, message: impl Into<String>) -> RelisError {
+    RelisError::Glob { pattern: pattern.to_string(), message: message.into() }
+}
+
+// Run a command listing the files of the pattern and return its output, its errors are shown in the terminal.
+fn run(pattern: &str, command: &mut Command) -> Result<String, RelisError> {
+    let output = command.stderr(Stdio::inherit()).output().map_err(|e| spawn_error(pattern, command, e))?;
+    if !output.status.success() {
+        let message = format!("{} failed ({})", command.get_program().to_string_lossy(), output.status);
+        return Err(RelisError::io(Path::new(pattern), std::io::ErrorKind::Other, message));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn spawn_error(path: &str, command: &Command, e: std::io::Error) -> RelisError {
+    RelisError::io(Path::new(path), e.kind(), format!("failed to run {}: {}", command.get_program().to_string_lossy(), e))
+}
+
+// Quote a string for a POSIX shell.
+fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hosts_starting_with_a_dash_are_rejected() {
+        let url = "ssh://-oProxyCommand=touch${IFS}/tmp/x/path";
+        assert!(split_url(url, url.strip_prefix("ssh://").unwrap()).is_err());
+        assert_eq!(split_url("ssh://host/a/b", "host/a/b").unwrap(), ("host", "a/b"));
+    }
+}
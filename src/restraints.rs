@@ -0,0 +1,25 @@
+// Restraint energies printed by sander when positional or NMR restraints are active: the energy without the
+// restraints ("EAMBER (non-restraint)  =  -1234.5"), read as EAMBER, and the subtotals of the NMR restraints
+// ("NMR restraints: Bond =  0.123  Angle = 0.000  Torsion = 0.000"), read as NMR_Bond, NMR_Angle, NMR_Torsion.
+// The total restraint energy is the RESTRAINT field of the frames.
+use std::collections::BTreeMap;
+use crate::RelisError;
+
+pub const NMR_PREFIX: &str = "NMR_";
+
+// Read a line of restraint energies, its values are added to data. Returns false if the line is another line.
+pub fn line(line: &str, data: &mut BTreeMap<String, Vec<f64>>) -> Result<bool, RelisError> {
+    let line = line.trim_start();
+    if let Some(rest) = line.strip_prefix("EAMBER (non-restraint)") {
+        let value = rest.trim_start().trim_start_matches('=').split_whitespace().next().unwrap_or_default();
+        data.entry("EAMBER".to_string()).or_default().push(value.parse::<f64>()?);
+        return Ok(true);
+    }
+    if let Some(rest) = line.strip_prefix("NMR restraints:") {
+        for (name, value) in crate::named_values(rest)? {
+            data.entry(format!("{}{}", NMR_PREFIX, name)).or_default().push(value);
+        }
+        return Ok(true);
+    }
+    Ok(false)
+}
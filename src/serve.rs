@@ -0,0 +1,153 @@
+use std::error::Error;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+use clap::Args;
+use polars::prelude::*;
+use serde_json::{json, Value};
+use crate::watch::Watcher;
+use crate::InputArgs;
+
+#[derive(Args)]
+pub struct ServeArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    /// Port to listen on.
+    #[arg(long, default_value_t = 8080)]
+    port: u16,
+    /// Address to listen on, use 0.0.0.0 to accept connections from other machines.
+    #[arg(long, default_value = "127.0.0.1")]
+    bind: String,
+}
+
+// Answer HTTP requests with the current data of the files matching the pattern.
+// The files are read again (only their new lines) at each request, so the answers follow running simulations.
+// GET /             HTML page with the summary.
+// GET /summary.json Mean and standard deviation of each column, and frames read per file.
+// GET /data.json    The whole table, one array of values per column.
+pub fn serve(args: &ServeArgs) -> Result<(), Box<dyn Error>> {
+    let mut watcher = Watcher::new(&args.input)?;
+    let listener = TcpListener::bind((args.bind.as_str(), args.port))?;
+    println!("Serving on http://{}:{} (Ctrl-C to stop)", args.bind, args.port);
+    for stream in listener.incoming() {
+        // A failing connection must not stop the server.
+        if let Err(e) = stream.map_err(|e| e.into()).and_then(|s| handle(s, &mut watcher)) {
+            println!("Request failed: {}", e);
+        }
+    }
+    Ok(())
+}
+
+// Time after which a client that sends or reads nothing is left, so that it does not block the others.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn handle(mut stream: TcpStream, watcher: &mut Watcher) -> Result<(), Box<dyn Error>> {
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Skip the headers, the requests have no body.
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let path = target.split('?').next().unwrap_or("");
+    if method != "GET" {
+        return respond(&mut stream, "405 Method Not Allowed", "text/plain", "Only GET is supported\n");
+    }
+    if !matches!(path, "/" | "/index.html" | "/summary.json" | "/data.json") {
+        return respond(&mut stream, "404 Not Found", "text/plain", "Not found\n");
+    }
+    watcher.poll()?;
+    let df = watcher.dataframe()?;
+    match path {
+        "/summary.json" => respond(&mut stream, "200 OK", "application/json", &summary_json(watcher, &df)?.to_string()),
+        "/data.json" => respond(&mut stream, "200 OK", "application/json", &data_json(&df)?.to_string()),
+        _ => respond(&mut stream, "200 OK", "text/html; charset=utf-8", &html_page(watcher, &df)?),
+    }
+}
+
+fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &str) -> Result<(), Box<dyn Error>> {
+    write!(stream, "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status, content_type, body.len(), body)?;
+    Ok(())
+}
+
+fn summary_json(watcher: &Watcher, df: &DataFrame) -> Result<Value, Box<dyn Error>> {
+    let files: Vec<Value> = watcher.files.iter().map(|(file, watched)| json!({
+        "file": file.display().to_string(),
+        "frames": watched.frame_count(),
+    })).collect();
+    let columns: serde_json::Map<String, Value> = crate::summary_stats(df)?
+        .into_iter()
+        .map(|stats| (stats.name, json!({ "mean": stats.mean, "std": stats.std })))
+        .collect();
+    Ok(json!({ "frames": df.height(), "files": files, "columns": columns }))
+}
+
+fn data_json(df: &DataFrame) -> Result<Value, Box<dyn Error>> {
+    let mut columns = serde_json::Map::new();
+    for col in df.get_columns() {
+        // The run column holds names, the other columns are numbers (integers after a join, for example).
+        let values = if col.dtype() == &DataType::Utf8 {
+            json!(col.utf8()?.into_iter().collect::<Vec<_>>())
+        } else {
+            json!(col.cast(&DataType::Float64)?.f64()?.into_iter().collect::<Vec<_>>())
+        };
+        columns.insert(col.name().to_string(), values);
+    }
+    Ok(Value::Object(columns))
+}
+
+fn html_page(watcher: &Watcher, df: &DataFrame) -> Result<String, Box<dyn Error>> {
+    let mut rows = String::new();
+    for stats in crate::summary_stats(df)? {
+        rows.push_str(&format!("<tr><td>{}</td><td>{:.4}</td><td>{:.4}</td></tr>\n",
+            escape(&stats.name), stats.mean, stats.std));
+    }
+    let mut files = String::new();
+    for (file, watched) in &watcher.files {
+        let frames = watched.frame_count();
+        files.push_str(&format!("<tr><td>{}</td><td>{}</td></tr>\n", escape(&file.display().to_string()), frames));
+    }
+    Ok(format!(r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta http-equiv="refresh" content="30">
+<title>relis</title>
+<style>body {{ font-family: sans-serif; }} td, th {{ padding: 2px 12px; text-align: right; }}</style>
+</head>
+<body>
+<h1>relis</h1>
+<p>{} frames from {} files. Raw data: <a href="summary.json">summary.json</a>, <a href="data.json">data.json</a>.</p>
+<table>
+<tr><th>Column</th><th>Mean</th><th>Std</th></tr>
+{}</table>
+<h2>Files</h2>
+<table>
+<tr><th>File</th><th>Frames</th></tr>
+{}</table>
+</body>
+</html>
+"#, df.height(), watcher.files.len(), rows, files))
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn data_json_casts_the_columns() {
+        let df = df!("run" => ["a", "b"], "NSTEP" => [1i64, 2], "Etot" => [Some(-1.5), None]).unwrap();
+        let data = data_json(&df).unwrap();
+        assert_eq!(data, json!({ "run": ["a", "b"], "NSTEP": [1.0, 2.0], "Etot": [-1.5, null] }));
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::time::{Duration, Instant};
+use clap::Args;
+use polars::prelude::*;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::symbols::Marker;
+use ratatui::widgets::{Axis, Block, Chart, Dataset, GraphType, List, Paragraph, Row, Table};
+use ratatui::{DefaultTerminal, Frame};
+use crate::fields::FieldParser;
+use crate::watch::Watcher;
+use crate::InputArgs;
+
+// Number of warnings kept for display.
+const MAX_WARNINGS: usize = 100;
+
+#[derive(Args)]
+pub struct TuiArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    /// Observables to plot, comma separated.
+    #[arg(long, value_delimiter = ',', default_value = "TEMP(K),Etot,PRESS,Density",
+        value_parser = FieldParser, hide_possible_values = true)]
+    plot: Vec<String>,
+    /// Seconds to wait between two checks of the files.
+    #[arg(long, default_value_t = 10.0)]
+    interval: f64,
+}
+
+// State of the dashboard.
+struct App {
+    watcher: Watcher,
+    df: DataFrame,
+    warnings: VecDeque<String>,
+}
+
+impl App {
+    // Read the new lines of the files and collect their warnings.
+    fn update(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.watcher.poll()? {
+            self.df = self.watcher.dataframe()?;
+        }
+        for (file, watched) in self.watcher.files.iter_mut() {
+            let name = file.file_name().unwrap_or_default().to_string_lossy();
+            for warning in watched.warnings.drain(..) {
+                self.warnings.push_front(format!("{}: {}", name, warning));
+            }
+        }
+        self.warnings.truncate(MAX_WARNINGS);
+        Ok(())
+    }
+}
+
+// Show a live dashboard of the files matching the pattern: plots of the selected observables,
+// progress of each run and the recent warnings. Runs until 'q' or 'Esc' is pressed.
+pub fn tui(args: &TuiArgs) -> Result<(), Box<dyn Error>> {
+    let mut app = App {
+        watcher: Watcher::new(&args.input)?,
+        df: DataFrame::default(),
+        warnings: VecDeque::new(),
+    };
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, &mut app, args);
+    ratatui::restore();
+    result
+}
+
+fn run(terminal: &mut DefaultTerminal, app: &mut App, args: &TuiArgs) -> Result<(), Box<dyn Error>> {
+    let interval = Duration::from_secs_f64(args.interval);
+    let mut last_update: Option<Instant> = None;
+    loop {
+        if last_update.is_none_or(|t| t.elapsed() >= interval) {
+            app.update()?;
+            last_update = Some(Instant::now());
+        }
+        terminal.draw(|frame| draw(frame, app, &args.plot))?;
+        if event::poll(Duration::from_millis(250))? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, app: &App, plots: &[String]) {
+    let progress_height = (app.watcher.files.len() as u16 + 3).min(12);
+    let [header, charts, progress, warnings] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Fill(1),
+        Constraint::Length(progress_height),
+        Constraint::Length(8),
+    ])
+    .areas(frame.area());
+    let title = format!("relis - {} frames from {} files - q: quit", app.df.height(), app.watcher.files.len());
+    frame.render_widget(Paragraph::new(title).bold(), header);
+    let areas = Layout::horizontal(plots.iter().map(|_| Constraint::Fill(1))).split(charts);
+    for (col, area) in plots.iter().zip(areas.iter()) {
+        draw_chart(frame, &app.df, col, *area);
+    }
+    draw_progress(frame, app, progress);
+    let items: Vec<String> = app.warnings.iter().cloned().collect();
+    let list = List::new(items)
+        .style(Style::new().fg(Color::Yellow))
+        .block(Block::bordered().title("Warnings"));
+    frame.render_widget(list, warnings);
+}
+
+// Plot a column against TIME(PS), or against the frame number if there is no time.
+fn draw_chart(frame: &mut Frame, df: &DataFrame, col: &str, area: Rect) {
+    let block = Block::bordered().title(col.to_string());
+    let Some(points) = points(df, col) else {
+        frame.render_widget(Paragraph::new("No data").block(block), area);
+        return;
+    };
+    let (x_min, x_max) = bounds(points.iter().map(|p| p.0));
+    let (y_min, y_max) = bounds(points.iter().map(|p| p.1));
+    let dataset = Dataset::default()
+        .marker(Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::new().fg(Color::Cyan))
+        .data(&points);
+    let chart = Chart::new(vec![dataset])
+        .block(block)
+        .x_axis(Axis::default()
+            .bounds([x_min, x_max])
+            .labels([format!("{:.1}", x_min), format!("{:.1}", x_max)]))
+        .y_axis(Axis::default()
+            .bounds([y_min, y_max])
+            .labels([format!("{:.2}", y_min), format!("{:.2}", y_max)]));
+    frame.render_widget(chart, area);
+}
+
+fn points(df: &DataFrame, col: &str) -> Option<Vec<(f64, f64)>> {
+    let y = df.column(col).ok()?.f64().ok()?;
+    let time = relis::time_column(&df.get_column_names());
+    let points: Vec<(f64, f64)> = match time.and_then(|t| df.column(&t).ok()?.f64().ok().cloned()) {
+        Some(x) => x.into_iter().zip(y).filter_map(|(x, y)| Some((x?, y?))).collect(),
+        None => y.into_iter().enumerate().filter_map(|(i, y)| Some((i as f64, y?))).collect(),
+    };
+    if points.is_empty() {
+        return None;
+    }
+    Some(points)
+}
+
+// Minimum and maximum of the values, widened if they are equal so that the axis is not empty.
+fn bounds(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    let (min, max) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| (min.min(v), max.max(v)));
+    if min == max {
+        return (min - 1.0, max + 1.0);
+    }
+    (min, max)
+}
+
+// One row per file with the last step read out of the number of steps of the run.
+fn draw_progress(frame: &mut Frame, app: &App, area: Rect) {
+    let rows = app.watcher.files.iter().map(|(file, watched)| {
+        let last = |key: &str| watched.data.get(key).and_then(|v| v.last().copied());
+        let step = last("NSTEP");
+        let keys: Vec<&str> = watched.data.keys().map(String::as_str).collect();
+        let time = relis::time_column(&keys)
+            .and_then(|t| last(&t))
+            .map(|t| format!("{:.3}", t))
+            .unwrap_or_default();
+        let (steps, bar) = match (step, watched.nstlim) {
+            (Some(step), Some(nstlim)) if nstlim > 0.0 => {
+                (format!("{} / {}", step, nstlim), progress_bar(step / nstlim, 30))
+            }
+            (Some(step), _) => (step.to_string(), String::new()),
+            _ => (String::new(), String::new()),
+        };
+        let name = file.file_name().unwrap_or_default().to_string_lossy().to_string();
+        Row::new([name, steps, time, bar])
+    });
+    let widths = [Constraint::Fill(1), Constraint::Length(24), Constraint::Length(14), Constraint::Length(37)];
+    let table = Table::new(rows, widths)
+        .header(Row::new(["File", "Step", "Time (ps)", "Progress"]).bold())
+        .block(Block::bordered().title("Runs"));
+    frame.render_widget(table, area);
+}
+
+fn progress_bar(ratio: f64, width: usize) -> String {
+    let ratio = ratio.clamp(0.0, 1.0);
+    let done = (ratio * width as f64).round() as usize;
+    format!("{}{} {:3.0}%", "#".repeat(done), "-".repeat(width - done), ratio * 100.0)
+}
@@ -0,0 +1,271 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+use clap::Args;
+use polars::prelude::*;
+use regex::Regex;
+use crate::manifest::{Manifest, ParsedFile, SkippedFile};
+use relis::{Engine, Extractor};
+use crate::{InputArgs, OutputArgs};
+
+#[derive(Args)]
+pub struct WatchArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    #[command(flatten)]
+    output: OutputArgs,
+    /// Seconds to wait between two checks of the files.
+    #[arg(long, default_value_t = 10.0)]
+    interval: f64,
+    #[command(flatten)]
+    alerts: crate::alert::AlertArgs,
+}
+
+// Regex of the number of steps in the echo of the input, compiled once for all the updates of all the files.
+fn nstlim_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?i)nstlim\s*=\s*(\d+)").unwrap())
+}
+
+// What has been read so far from a file that is still being written.
+#[derive(Default)]
+pub(crate) struct WatchedFile {
+    // Number of bytes already parsed.
+    offset: u64,
+    // Created from the first bytes read, which tell the format of the file.
+    extractor: Option<Extractor>,
+    // Length of the file at the last update, while its format is not recognized from its first bytes.
+    undetected_len: Option<u64>,
+    pub(crate) data: BTreeMap<String, Vec<f64>>,
+    // Number of steps of the run, from the echo of the input file.
+    pub(crate) nstlim: Option<f64>,
+    // Warnings found in the new lines, to be taken by the caller.
+    pub(crate) warnings: Vec<String>,
+}
+
+impl WatchedFile {
+    // Parse the lines appended to the file since the last call.
+    // Returns true if new values were found.
+    fn update(&mut self, path: &Path, input: &InputArgs) -> Result<bool, Box<dyn Error>> {
+        let mut file = std::fs::File::open(path)?;
+        let len = file.metadata()?.len();
+        // A file shorter than what was already read has been rewritten (e.g. restarted run).
+        if len < self.offset {
+            *self = WatchedFile::default();
+        }
+        if len == self.offset || self.extractor.as_ref().is_some_and(Extractor::ended) {
+            return Ok(false);
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut buf = Vec::new();
+        file.take(len - self.offset).read_to_end(&mut buf)?;
+        // Only complete lines are parsed, the last one may still be written.
+        let Some(last) = buf.iter().rposition(|b| *b == b'\n') else {
+            return Ok(false);
+        };
+        if self.extractor.is_none() {
+            // A run that is starting may have written too little to tell its format: the file is read once it is
+            // recognized, it has its first DETECTION_BYTES, or it has stopped growing, rather than read as an AMBER
+            // file for good.
+            let engine = input.engine(&buf[..buf.len().min(relis::DETECTION_BYTES)]);
+            if engine.is_none() && buf.len() < relis::DETECTION_BYTES && self.undetected_len != Some(len) {
+                self.undetected_len = Some(len);
+                return Ok(false);
+            }
+            self.extractor = Some(input.extractor(engine.unwrap_or_default()));
+        }
+        self.offset += last as u64 + 1;
+        let values_before = self.value_count();
+        let extractor = self.extractor.as_mut().expect("the extractor is created above");
+        for line in relis::Lines::new(&buf[..=last]) {
+            let line = line?;
+            let line = line.as_str();
+            if self.nstlim.is_none() {
+                if let Some(cap) = nstlim_regex().captures(line) {
+                    self.nstlim = cap[1].parse().ok();
+                }
+            }
+            if relis::is_warning(line) {
+                self.warnings.push(line.trim().to_string());
+            }
+            extractor.line(line, &mut self.data)?;
+        }
+        Ok(self.value_count() > values_before)
+    }
+
+    fn value_count(&self) -> usize {
+        self.data.values().map(Vec::len).sum()
+    }
+
+    // True when the end of the values has been read (e.g. the averages of a finished AMBER run).
+    pub(crate) fn ended(&self) -> bool {
+        self.extractor.as_ref().is_some_and(Extractor::ended)
+    }
+
+    // Number of complete frames read.
+    pub(crate) fn frame_count(&self) -> usize {
+        self.data.values().map(Vec::len).min().unwrap_or(0)
+    }
+
+    // The values of the complete frames, a frame still being written is left out.
+    pub(crate) fn frames(&self) -> BTreeMap<String, Vec<f64>> {
+        let n = self.frame_count();
+        self.data
+            .iter()
+            .map(|(key, values)| (key.clone(), values[..n].to_vec()))
+            .collect()
+    }
+}
+
+// The files matching a pattern, read as they grow.
+pub(crate) struct Watcher {
+    input: InputArgs,
+    path: PathBuf,
+    pattern: String,
+    // The files given with --files-from, read once as the list can come from stdin, or given as paths.
+    list: Option<Vec<PathBuf>>,
+    pub(crate) files: BTreeMap<PathBuf, WatchedFile>,
+}
+
+impl Watcher {
+    pub(crate) fn new(input: &InputArgs) -> Result<Watcher, Box<dyn Error>> {
+        if input.patterns.iter().any(|p| crate::remote::is_remote(p) || crate::archive::is_archive(p)) {
+            return Err("Only local files can be watched, not remote files or members of archives".into());
+        }
+        if input.patterns.len() > 1 {
+            return Err("Only one pattern can be watched, use --files-from to watch files of several directories".into());
+        }
+        let (path, pattern, files) = crate::find_files(input)?;
+        let list = (input.files_from.is_some() || input.literal()).then_some(files);
+        Ok(Watcher { input: input.clone(), path, pattern, list, files: BTreeMap::new() })
+    }
+
+    // Parse the lines appended to the matching files (new files included) since the last call.
+    // Returns true if new values were found. A file that cannot be read (e.g. removed or rotated after the listing,
+    // or with a line that fails to parse) is reported and left for the next call, the other files are read.
+    pub(crate) fn poll(&mut self) -> Result<bool, Box<dyn Error>> {
+        let mut updated = false;
+        let files = match &self.list {
+            Some(files) => files.clone(),
+            None => self.input.select_files(crate::list_files(&self.path, &self.pattern)?)?,
+        };
+        for file in files {
+            match self.files.entry(file.clone()).or_default().update(&file, &self.input) {
+                Ok(file_updated) => updated |= file_updated,
+                Err(e) => println!("Failed to read {}: {}", file.display(), e),
+            }
+        }
+        Ok(updated)
+    }
+
+    // A manifest describing the files read so far and the table built from them.
+    fn manifest(&self, df: &DataFrame) -> Manifest {
+        let mut manifest = Manifest::new(&self.path, &self.pattern);
+        for (file, watched) in &self.files {
+            if watched.data.is_empty() {
+                manifest.skipped.push(SkippedFile { path: file.clone(), reason: "no data found yet".to_string() });
+            } else {
+                manifest.files.push(ParsedFile {
+                    path: file.clone(),
+                    engine: watched.extractor.as_ref().map_or(Engine::Amber, Extractor::engine).name().to_string(),
+                    frames: watched.frame_count(),
+                    columns: watched.data.keys().cloned().collect(),
+                    missing_columns: Vec::new(),
+                });
+            }
+        }
+        manifest.columns = df.get_column_names().iter().map(|c| c.to_string()).collect();
+        manifest.frames = df.height();
+        manifest
+    }
+
+    // The complete frames of all the files in a single DataFrame, sorted by time.
+    pub(crate) fn dataframe(&self) -> Result<DataFrame, Box<dyn Error>> {
+        let mut df = DataFrame::new::<Series>(vec![])?;
+        for watched in self.files.values().filter(|w| !w.data.is_empty()) {
+            df = crate::stack(&df, relis::to_dataframe(&watched.frames())?)?;
+        }
+        Ok(crate::build_table(df, &self.input)?)
+    }
+}
+
+// Monitor the files matching the pattern, parse the new lines when they grow,
+// and update the CSV file and the averages printed in the terminal. Runs until interrupted.
+pub fn watch(args: &WatchArgs) -> Result<(), Box<dyn Error>> {
+    if args.output.dry_run {
+        return crate::dry_run::dry_run(&args.input);
+    }
+    if args.output.partition_by.is_some() {
+        return Err("--partition-by cannot be used when watching".into());
+    }
+    let mut watcher = Watcher::new(&args.input)?;
+    // The outputs are then rewritten at each update.
+    let paths = args.output.paths(&watcher.path)?;
+    let interval = Duration::from_secs_f64(args.interval);
+    println!("Watching the files (Ctrl-C to stop)");
+    let mut warnings = Vec::new();
+    let mut alerts = crate::alert::Alerts::new(&args.alerts);
+    loop {
+        // The files are listed again at the next check, the watch goes on.
+        let updated = watcher.poll().unwrap_or_else(|e| {
+            println!("Failed to list the files: {}", e);
+            false
+        });
+        alerts.check(&watcher.files);
+        for (file, watched) in watcher.files.iter_mut() {
+            for warning in watched.warnings.drain(..) {
+                println!("Warning in {}: {}", file.display(), warning);
+                warnings.push(format!("{}: {}", file.display(), warning));
+            }
+        }
+        if updated {
+            let mut df = watcher.dataframe()?;
+            if !df.is_empty() {
+                println!("\n{} frames read from {} files", df.height(), watcher.files.len());
+                let production = crate::production_rows(&df, &args.output)?;
+                let outputs = crate::save_outputs(&mut df, &paths, &args.output)?;
+                if let Some(manifest_path) = &args.output.manifest {
+                    let mut manifest = watcher.manifest(&df);
+                    manifest.warnings = warnings.clone();
+                    manifest.outputs = outputs;
+                    manifest.write(manifest_path)?;
+                }
+                crate::print_summary(&df, production.as_ref(), &args.output)?;
+            }
+        }
+        std::thread::sleep(interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        input: InputArgs,
+    }
+
+    #[test]
+    fn format_is_detected_once_enough_is_written() {
+        let input = Cli::parse_from(["relis", "*.log"]).input;
+        let path = std::env::temp_dir().join(format!("relis-watch-test-{}.log", std::process::id()));
+        std::fs::write(&path, "Charm++: standalone mode (not using charmrun)\n").unwrap();
+        let mut watched = WatchedFile::default();
+        assert!(!watched.update(&path, &input).unwrap());
+        assert!(watched.extractor.is_none());
+
+        let log = "Charm++: standalone mode (not using charmrun)\nInfo: NAMD 2.14 for Linux-x86_64-multicore\n\
+                   ETITLE:      TS           TEMP\nENERGY:       0       300.0000\n";
+        std::fs::write(&path, log).unwrap();
+        assert!(watched.update(&path, &input).unwrap());
+        assert_eq!(watched.extractor.as_ref().map(Extractor::engine), Some(relis::Engine::Namd));
+        assert_eq!(watched.data["TEMP(K)"], vec![300.0]);
+        std::fs::remove_file(path).unwrap();
+    }
+}